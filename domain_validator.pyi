@@ -0,0 +1,43 @@
+from typing import Iterable, Optional, TypedDict
+
+class PslMetadata(TypedDict):
+    version: Optional[str]
+    commit: Optional[str]
+    icann_rules: int
+    private_rules: int
+    loaded_at: int
+    source: str
+
+class DomainParts(TypedDict):
+    suffix: str
+    registrable_domain: Optional[str]
+    subdomain: Optional[str]
+    section: Optional[str]
+
+class SuffixStore:
+    def __init__(self, path: Optional[str] = None) -> None: ...
+    @staticmethod
+    def from_compiled_file(path: str) -> SuffixStore: ...
+    @staticmethod
+    def from_string(data: str) -> SuffixStore: ...
+    @property
+    def metadata(self) -> PslMetadata: ...
+    def extract_root_domain(self, domain: str) -> str: ...
+    def extract_root_domain_from_url(self, url: str) -> str: ...
+    def split_host(self, host: str) -> DomainParts: ...
+    def extract_root_domains(
+        self, domains: Iterable[Optional[str]], strict: bool = False
+    ) -> list[Optional[str]]: ...
+    def extract_root_domains_from_urls(
+        self, urls: Iterable[Optional[str]], strict: bool = False
+    ) -> list[Optional[str]]: ...
+
+def parse_url(url: str) -> str: ...
+def extract_root_domain(domain: str) -> str: ...
+def extract_root_domain_from_url(url: str) -> str: ...
+def extract_root_domains(
+    domains: Iterable[Optional[str]], strict: bool = False
+) -> list[Optional[str]]: ...
+def extract_root_domains_from_urls(
+    urls: Iterable[Optional[str]], strict: bool = False
+) -> list[Optional[str]]: ...
@@ -0,0 +1,160 @@
+#ifndef DOMAIN_VALIDATOR_H
+#define DOMAIN_VALIDATOR_H
+
+/* Generated by cbindgen from src/ffi.rs. Do not edit by hand. */
+
+#include <stddef.h>
+#include <stdint.h>
+
+// The call succeeded
+#define DV_OK 0
+
+// A required pointer argument was NULL
+#define DV_ERR_NULL_POINTER 1
+
+// A string argument was not valid UTF-8
+#define DV_ERR_INVALID_UTF8 2
+
+// The Public Suffix List could not be loaded or parsed
+#define DV_ERR_LOAD_FAILED 3
+
+// The input is not a valid URL or has no host
+#define DV_ERR_INVALID_URL 4
+
+// The URL host is an IPv4 or IPv6 address
+#define DV_ERR_IP_ADDRESS 5
+
+// The input is not a valid domain name
+#define DV_ERR_INVALID_DOMAIN 6
+
+// The domain has no registrable part (e.g. it is a public suffix)
+#define DV_ERR_NO_ROOT_DOMAIN 7
+
+// The caller's buffer is too small; the required length has been reported
+#define DV_ERR_BUFFER_TOO_SMALL 8
+
+// An unexpected internal error occurred
+#define DV_ERR_INTERNAL 9
+
+// Opaque handle to a loaded Public Suffix List
+typedef struct DvSuffixStore DvSuffixStore;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Creates a store from the Public Suffix List embedded in the library.
+//
+// The returned handle must be released with `dv_store_free`. Returns NULL
+// only if an internal error occurs.
+struct DvSuffixStore *dv_store_new_embedded(void);
+
+// Loads a store from a `public_suffix_list.dat` file.
+//
+// On success `*out` receives a handle that must be released with
+// `dv_store_free`.
+//
+// # Safety
+//
+// `path` must be a NUL-terminated string and `out` a valid pointer.
+int32_t dv_store_new_from_file(const char *path, struct DvSuffixStore **out);
+
+// Releases a store. Passing NULL is a no-op.
+//
+// # Safety
+//
+// `store` must be NULL or a handle returned by this library that has not
+// been freed yet.
+void dv_store_free(struct DvSuffixStore *store);
+
+// Extracts the normalized host of a URL into a caller buffer.
+//
+// The result is written NUL-terminated to `buf`. `*out_len` (if not NULL)
+// receives the length of the result without the terminator, also when the
+// buffer is too small, so callers can pass a NULL buffer to query the size.
+//
+// # Safety
+//
+// `url` must be a NUL-terminated string, `buf` must be NULL or point to at
+// least `buf_len` writable bytes, and `out_len` must be NULL or valid.
+int32_t dv_parse_url(const char *url, char *buf, size_t buf_len, size_t *out_len);
+
+// Extracts the root domain (eTLD+1) of a domain into a caller buffer.
+//
+// See `dv_parse_url` for the buffer contract.
+//
+// # Safety
+//
+// `store` must be a valid handle, `domain` a NUL-terminated string, `buf`
+// NULL or at least `buf_len` writable bytes, and `out_len` NULL or valid.
+int32_t dv_extract_root_domain(const struct DvSuffixStore *store,
+                               const char *domain,
+                               char *buf,
+                               size_t buf_len,
+                               size_t *out_len);
+
+// Extracts the root domain (eTLD+1) of a URL into a caller buffer.
+//
+// The root domain is in Unicode when the URL spells its host that way, as
+// with `SuffixStore::extract_root_domain_from_url`. See `dv_parse_url` for
+// the buffer contract.
+//
+// # Safety
+//
+// `store` must be a valid handle, `url` a NUL-terminated string, `buf`
+// NULL or at least `buf_len` writable bytes, and `out_len` NULL or valid.
+int32_t dv_extract_root_domain_from_url(const struct DvSuffixStore *store,
+                                        const char *url,
+                                        char *buf,
+                                        size_t buf_len,
+                                        size_t *out_len);
+
+// Extracts the root domain of a domain as a newly allocated string.
+//
+// On success `*out` receives a NUL-terminated string that must be released
+// with `dv_string_free`.
+//
+// # Safety
+//
+// `store` must be a valid handle, `domain` a NUL-terminated string and
+// `out` a valid pointer.
+int32_t dv_extract_root_domain_owned(const struct DvSuffixStore *store,
+                                     const char *domain,
+                                     char **out);
+
+// Extracts the root domain of a URL as a newly allocated string, in
+// Unicode when the URL spells its host that way.
+//
+// On success `*out` receives a NUL-terminated string that must be released
+// with `dv_string_free`.
+//
+// # Safety
+//
+// `store` must be a valid handle, `url` a NUL-terminated string and `out`
+// a valid pointer.
+int32_t dv_extract_root_domain_from_url_owned(const struct DvSuffixStore *store,
+                                              const char *url,
+                                              char **out);
+
+// Releases a string returned by this library. Passing NULL is a no-op.
+//
+// # Safety
+//
+// `s` must be NULL or a string returned by a `*_owned` function that has
+// not been freed yet.
+void dv_string_free(char *s);
+
+// Returns the message of the last error on the calling thread, or NULL.
+//
+// The pointer stays valid until the next call into this library on the
+// same thread and must not be freed.
+const char *dv_last_error_message(void);
+
+// Returns a static description of a status code. The result must not be freed.
+const char *dv_status_description(int32_t status);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* DOMAIN_VALIDATOR_H */
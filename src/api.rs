@@ -1,21 +1,119 @@
 use axum::{
-    extract::Query,
+    extract::{FromRef, Query, State},
     http::StatusCode,
     response::{IntoResponse, Json},
     routing::get,
     Router,
 };
 use serde::{Deserialize, Serialize};
+use std::env;
 use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
 use tokio::net::TcpListener;
-use tracing::info;
+use tracing::{info, warn};
+use tracing_subscriber::EnvFilter;
 
-use crate::domain_parser;
+use crate::defang;
+use crate::domain::Domain;
+use crate::domain_parser::{self, Normalization, NormalizedHost, PunycodeIssue};
+use crate::homograph::{self, HomographReport};
+use crate::idn_display::DisplayDecision;
+use crate::idn_tables::{IdnTables, RepertoireReport};
+use crate::idna_options::IdnaOptions;
+use crate::lookalike::{Lookalike, ProtectedDomains};
+use crate::redirect::{Hop, Unwrapper};
+use crate::suffix_store::{PslMetadata, SuffixStore};
+use crate::typosquat::{self, Technique, Typosquat};
+use crate::url_diagnostics::{self, Diagnostic};
+
+/// Environment variable holding the maximum acceptable PSL age, in days
+pub const PSL_MAX_AGE_ENV: &str = "PSL_MAX_AGE_DAYS";
+
+/// Maximum acceptable PSL age used when `PSL_MAX_AGE_DAYS` is not set or invalid
+pub const DEFAULT_PSL_MAX_AGE_DAYS: u64 = 30;
+
+/// Seconds in a day, to convert `PSL_MAX_AGE_DAYS`
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// Environment variable holding the path of the protected domains file
+pub const PROTECTED_DOMAINS_ENV: &str = "PROTECTED_DOMAINS_FILE";
+
+/// Environment variable holding the path of a file with extra unwrap rules
+pub const UNWRAP_RULES_ENV: &str = "UNWRAP_RULES_FILE";
+
+/// Shared state of the API handlers
+#[derive(Clone)]
+pub struct AppState {
+    /// The Public Suffix List used to answer requests
+    pub store: Arc<SuffixStore>,
+    /// The domains `/lookalike` checks hosts against
+    pub protected: Arc<ProtectedDomains>,
+    /// The rules `/parse?unwrap=true` follows redirect wrappers with
+    pub unwrapper: Arc<Unwrapper>,
+}
+
+impl FromRef<AppState> for Arc<SuffixStore> {
+    fn from_ref(state: &AppState) -> Self {
+        state.store.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<ProtectedDomains> {
+    fn from_ref(state: &AppState) -> Self {
+        state.protected.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<Unwrapper> {
+    fn from_ref(state: &AppState) -> Self {
+        state.unwrapper.clone()
+    }
+}
 
 /// Request parameters for the domain parsing endpoint
 #[derive(Debug, Deserialize)]
 pub struct ParseParams {
     url: String,
+    #[serde(default)]
+    debug: bool,
+    /// Adds the browser-style display decision for the root domain
+    #[serde(default)]
+    display: bool,
+    /// Adds the transformations that turned the URL into its host
+    #[serde(default)]
+    normalizations: bool,
+    /// Follows redirect and safelink wrappers to the destination before
+    /// extracting its root domain
+    #[serde(default)]
+    unwrap: bool,
+    /// Refangs the URL before parsing it, see [`defang::refang`]
+    #[serde(default)]
+    refang: bool,
+    /// Adds defanged forms of the URL and root domain, safe to show in reports
+    #[serde(default)]
+    defang: bool,
+    /// IDNA options overriding those of the store, see [`IdnaOptions`]
+    transitional_processing: Option<bool>,
+    use_std3_ascii_rules: Option<bool>,
+    check_hyphens: Option<bool>,
+    check_bidi: Option<bool>,
+    check_joiners: Option<bool>,
+    verify_dns_length: Option<bool>,
+}
+
+impl ParseParams {
+    /// Applies the IDNA options given in the query to the store's own
+    fn idna_options(&self, defaults: &IdnaOptions) -> IdnaOptions {
+        IdnaOptions {
+            transitional_processing: self.transitional_processing.unwrap_or(defaults.transitional_processing),
+            use_std3_ascii_rules: self.use_std3_ascii_rules.unwrap_or(defaults.use_std3_ascii_rules),
+            check_hyphens: self.check_hyphens.unwrap_or(defaults.check_hyphens),
+            check_bidi: self.check_bidi.unwrap_or(defaults.check_bidi),
+            check_joiners: self.check_joiners.unwrap_or(defaults.check_joiners),
+            verify_dns_length: self.verify_dns_length.unwrap_or(defaults.verify_dns_length),
+        }
+    }
 }
 
 /// Response structure for the domain parsing endpoint
@@ -24,59 +122,400 @@ pub struct ParseResponse {
     pub original_url: String,
     pub root_domain: Option<String>,
     pub error: Option<String>,
+    /// Mixed-script and confusable checks, only present for internationalized hosts
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub homograph: Option<HomographReport>,
+    /// The registrable label checked against its TLD's repertoire, only
+    /// present for internationalized hosts under a TLD with a table
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repertoire: Option<RepertoireReport>,
+    /// Every URL from the original one to the destination, only present when `unwrap=true` is requested
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub redirect_chain: Option<Vec<Hop>>,
+    /// The URL that was parsed, only present when `refang=true` is requested
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refanged_url: Option<String>,
+    /// The parsed URL, defanged, only present when `defang=true` is requested
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub defanged_url: Option<String>,
+    /// The root domain, defanged, only present when `defang=true` is requested
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub defanged_root_domain: Option<String>,
+    /// Problems located in the URL that was parsed, only present when some
+    /// are found. Their spans are byte offsets into that URL: the last URL of
+    /// `redirect_chain` when unwrapping, else `refanged_url` when refanging,
+    /// else `original_url`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub diagnostics: Vec<Diagnostic>,
+    /// The URL that was parsed with the suggested fixes of `diagnostics` applied, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub suggestion: Option<String>,
+    /// Fake or broken punycode labels in the host, only present when some are found
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub punycode: Vec<PunycodeIssue>,
+    /// The transformations from the URL to its host, only present when `normalizations=true` is requested
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub normalizations: Option<Vec<Normalization>>,
+    /// How browsers would display the root domain, only present when `display=true` is requested
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display: Option<DisplayDecision>,
+    /// Diagnostic information, only present when `debug=true` is requested
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub debug: Option<DebugInfo>,
+}
+
+/// Diagnostic information attached to responses on request
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DebugInfo {
+    /// The Public Suffix List used to answer the request
+    pub psl: PslMetadata,
+}
+
+impl DebugInfo {
+    /// Diagnostic information for a request answered with `store`, if it was requested
+    fn requested(debug: bool, store: &SuffixStore) -> Option<Self> {
+        debug.then(|| DebugInfo {
+            psl: store.metadata().clone(),
+        })
+    }
 }
 
 /// Handler for the domain parsing endpoint
-async fn parse_domain(Query(params): Query<ParseParams>) -> impl IntoResponse {
+async fn parse_domain(
+    State(store): State<Arc<SuffixStore>>,
+    State(unwrapper): State<Arc<Unwrapper>>,
+    Query(params): Query<ParseParams>,
+) -> impl IntoResponse {
     info!("Received request to parse URL: {}", params.url);
-    
+
+    let refanged_url = params.refang.then(|| defang::refang(&params.url));
+    let mut url = refanged_url.clone().unwrap_or_else(|| params.url.clone());
+    let mut redirect_chain = None;
+    let mut unwrap_error = None;
+    if params.unwrap {
+        match unwrapper.unwrap_url(&store, &url) {
+            Ok(chain) => {
+                url = chain.last().map_or(url, |destination| destination.url.clone());
+                redirect_chain = Some(chain);
+            },
+            Err(e) => {
+                unwrap_error = Some(format!("Could not follow redirects: {}", e));
+            }
+        }
+    }
+
     let mut response = ParseResponse {
         original_url: params.url.clone(),
         root_domain: None,
-        error: None,
+        error: unwrap_error,
+        homograph: None,
+        repertoire: None,
+        redirect_chain,
+        refanged_url,
+        defanged_url: params.defang.then(|| defang::defang(&url)),
+        defanged_root_domain: None,
+        diagnostics: url_diagnostics::diagnose_with(&store, &url),
+        suggestion: None,
+        punycode: domain_parser::check_url_punycode(&url),
+        normalizations: None,
+        display: None,
+        debug: None,
     };
-    
-    match domain_parser::extract_root_domain_from_url(&params.url) {
+
+    response.suggestion = url_diagnostics::apply_suggestions(&url, &response.diagnostics);
+
+    let options = params.idna_options(store.idna_options());
+    match store.extract_root_domain_from_url_with(&url, &options) {
         Ok(domain) => {
+            if params.defang {
+                response.defanged_root_domain = Some(defang::defang(&domain));
+            }
             response.root_domain = Some(domain);
         },
+        Err(e) => {
+            // An unwrap error explains why the URL could not be parsed either
+            response.error.get_or_insert(e);
+        }
+    }
+
+    if let Ok(NormalizedHost { host, normalizations }) = domain_parser::normalize_url_with(&url, &options) {
+        if params.normalizations {
+            response.normalizations = Some(normalizations);
+        }
+        if homograph::is_idn(&host) {
+            response.homograph = Some(homograph::analyze(&host));
+            response.repertoire = IdnTables::builtin().check(&store, &host).ok().flatten();
+        }
+        if params.display {
+            response.display = store.display_root_domain_with(&host, &options).ok();
+        }
+    }
+
+    response.debug = DebugInfo::requested(params.debug, &store);
+
+    (StatusCode::OK, Json(response))
+}
+
+/// Request parameters for the typosquatting endpoint
+#[derive(Debug, Deserialize)]
+pub struct TyposquatParams {
+    domain: String,
+    /// Comma-separated technique names; all techniques when absent
+    techniques: Option<String>,
+    #[serde(default)]
+    debug: bool,
+}
+
+/// Response structure for the typosquatting endpoint
+#[derive(Debug, Serialize)]
+pub struct TyposquatResponse {
+    pub domain: String,
+    pub root_domain: Option<String>,
+    pub candidates: Vec<Typosquat>,
+    pub error: Option<String>,
+    /// Diagnostic information, only present when `debug=true` is requested
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub debug: Option<DebugInfo>,
+}
+
+/// Handler for the typosquatting endpoint
+async fn typosquats(
+    State(store): State<Arc<SuffixStore>>,
+    Query(params): Query<TyposquatParams>,
+) -> impl IntoResponse {
+    info!("Received request for typosquats of: {}", params.domain);
+
+    let mut response = TyposquatResponse {
+        domain: params.domain.clone(),
+        root_domain: None,
+        candidates: Vec::new(),
+        error: None,
+        debug: DebugInfo::requested(params.debug, &store),
+    };
+
+    let result = parse_techniques(params.techniques.as_deref()).and_then(|techniques| {
+        let root = Domain::parse(&params.domain)?.root_domain_in(&store)?;
+        let candidates = typosquat::generate(&store, &root, &techniques);
+        Ok((root, candidates))
+    });
+
+    match result {
+        Ok((root, candidates)) => {
+            response.root_domain = Some(root.into());
+            response.candidates = candidates;
+        },
         Err(e) => {
             response.error = Some(e);
         }
     }
-    
+
+    (StatusCode::OK, Json(response))
+}
+
+/// Parses the comma-separated `techniques` parameter
+fn parse_techniques(techniques: Option<&str>) -> Result<Vec<Technique>, String> {
+    match techniques {
+        None => Ok(Technique::ALL.to_vec()),
+        Some(list) => list
+            .split(',')
+            .map(|name| name.trim().parse())
+            .collect(),
+    }
+}
+
+/// Request parameters for the lookalike endpoint
+#[derive(Debug, Deserialize)]
+pub struct LookalikeParams {
+    url: String,
+    #[serde(default)]
+    debug: bool,
+}
+
+/// Response structure for the lookalike endpoint
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LookalikeResponse {
+    pub original_url: String,
+    /// The protected domain the URL's host imitates, if any
+    pub lookalike: Option<Lookalike>,
+    pub error: Option<String>,
+    /// Diagnostic information, only present when `debug=true` is requested
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub debug: Option<DebugInfo>,
+}
+
+/// Handler for the lookalike endpoint
+async fn lookalike(
+    State(store): State<Arc<SuffixStore>>,
+    State(protected): State<Arc<ProtectedDomains>>,
+    Query(params): Query<LookalikeParams>,
+) -> impl IntoResponse {
+    info!("Received request to check URL for lookalikes: {}", params.url);
+
+    let mut response = LookalikeResponse {
+        original_url: params.url.clone(),
+        lookalike: None,
+        error: None,
+        debug: DebugInfo::requested(params.debug, &store),
+    };
+
+    if protected.is_empty() {
+        response.error = Some(format!("No protected domains are loaded (set {})", PROTECTED_DOMAINS_ENV));
+    } else {
+        match protected.check_url(&store, &params.url) {
+            Ok(lookalike) => {
+                response.lookalike = lookalike;
+            },
+            Err(e) => {
+                response.error = Some(e);
+            }
+        }
+    }
+
     (StatusCode::OK, Json(response))
 }
 
+/// Handler for the PSL information endpoint
+async fn psl_info(State(store): State<Arc<SuffixStore>>) -> Json<PslMetadata> {
+    Json(store.metadata().clone())
+}
+
 /// Handler for the root endpoint
 async fn root() -> &'static str {
-    "Domain Validator API\n\nUsage: GET /parse?url=<url>"
+    "Domain Validator API\n\nUsage: GET /parse?url=<url>\n       GET /typosquats?domain=<domain>[&techniques=<list>]\n       GET /lookalike?url=<url>\n       GET /psl/info"
 }
 
-/// Create and configure the API router
+/// Create and configure the API router with the default Public Suffix List
 pub fn create_router() -> Router {
+    let store = match SuffixStore::load_default() {
+        Ok(store) => store,
+        Err(e) => {
+            warn!("{}, falling back to the embedded list", e);
+            SuffixStore::embedded()
+        }
+    };
+
+    let protected = match load_protected_domains(&store) {
+        Ok(protected) => protected,
+        Err(e) => {
+            warn!("{}, no domains are protected", e);
+            ProtectedDomains::default()
+        }
+    };
+
+    let unwrapper = match load_unwrapper() {
+        Ok(unwrapper) => unwrapper,
+        Err(e) => {
+            warn!("{}, using the built-in unwrap rules only", e);
+            Unwrapper::builtin()
+        }
+    };
+
+    create_router_with_state(AppState {
+        store: Arc::new(store),
+        protected: Arc::new(protected),
+        unwrapper: Arc::new(unwrapper),
+    })
+}
+
+/// Create and configure the API router around an already loaded store, with
+/// no protected domains and the built-in unwrap rules
+pub fn create_router_with_store(store: Arc<SuffixStore>) -> Router {
+    create_router_with_state(AppState {
+        store,
+        protected: Arc::new(ProtectedDomains::default()),
+        unwrapper: Arc::new(Unwrapper::builtin()),
+    })
+}
+
+/// Create and configure the API router around already loaded state
+pub fn create_router_with_state(state: AppState) -> Router {
     Router::new()
         .route("/", get(root))
         .route("/parse", get(parse_domain))
+        .route("/typosquats", get(typosquats))
+        .route("/lookalike", get(lookalike))
+        .route("/psl/info", get(psl_info))
+        .with_state(state)
+}
+
+/// Loads the protected domains file named by `PROTECTED_DOMAINS_FILE`, if set
+fn load_protected_domains(store: &SuffixStore) -> Result<ProtectedDomains, String> {
+    match env::var(PROTECTED_DOMAINS_ENV) {
+        Ok(path) => ProtectedDomains::from_file(store, path),
+        Err(_) => Ok(ProtectedDomains::default()),
+    }
+}
+
+/// Builds the built-in unwrap rules, preceded by those of the file named by
+/// `UNWRAP_RULES_FILE`, if set
+fn load_unwrapper() -> Result<Unwrapper, String> {
+    let mut unwrapper = Unwrapper::builtin();
+    if let Ok(path) = env::var(UNWRAP_RULES_ENV) {
+        unwrapper.load_rules(path)?;
+    }
+    Ok(unwrapper)
+}
+
+/// Reads the maximum acceptable PSL age from `PSL_MAX_AGE_DAYS`
+fn psl_max_age() -> Duration {
+    let default = Duration::from_secs(DEFAULT_PSL_MAX_AGE_DAYS * SECONDS_PER_DAY);
+    let Ok(value) = env::var(PSL_MAX_AGE_ENV) else {
+        return default;
+    };
+
+    // Days too many to count in seconds are rejected like any invalid value
+    match value.parse::<u64>().ok().and_then(|days| days.checked_mul(SECONDS_PER_DAY)) {
+        Some(seconds) => Duration::from_secs(seconds),
+        None => {
+            warn!("Ignoring invalid {} value '{}'", PSL_MAX_AGE_ENV, value);
+            default
+        }
+    }
 }
 
 /// Start the web service
 pub async fn start_service() -> Result<(), Box<dyn std::error::Error>> {
-    // Initialize tracing
-    tracing_subscriber::fmt::init();
-    
+    // Initialize tracing, logging at info level unless RUST_LOG says otherwise
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+    tracing_subscriber::fmt().with_env_filter(filter).init();
+
+    // Load the Public Suffix List once for the lifetime of the service
+    let store = SuffixStore::load_default()?;
+    let metadata = store.metadata();
+    info!(
+        "Loaded Public Suffix List from {} (version {}, {} ICANN and {} private rules)",
+        metadata.source,
+        metadata.version.as_deref().unwrap_or("unknown"),
+        metadata.icann_rules,
+        metadata.private_rules
+    );
+    store.warn_if_stale(psl_max_age());
+
+    // Load the protected domains checked by /lookalike
+    let protected = load_protected_domains(&store)?;
+    if !protected.is_empty() {
+        info!("Loaded {} protected domains", protected.len());
+    }
+
+    // Load the unwrap rules followed by /parse?unwrap=true
+    let unwrapper = load_unwrapper()?;
+
     // Create the router
-    let app = create_router();
-    
+    let app = create_router_with_state(AppState {
+        store: Arc::new(store),
+        protected: Arc::new(protected),
+        unwrapper: Arc::new(unwrapper),
+    });
+
     // Define the address to bind to
     let addr = SocketAddr::from(([127, 0, 0, 1], 3000));
     info!("Starting server on {}", addr);
-    
+
     // Create a TCP listener
     let listener = TcpListener::bind(addr).await?;
-    
+
     // Start the server
     axum::serve(listener, app).await?;
-    
+
     Ok(())
-} 
\ No newline at end of file
+}
@@ -0,0 +1,280 @@
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::{BufRead, BufWriter, Read, Write};
+
+use crate::domain_parser::DomainErrorKind;
+use crate::suffix_store::SuffixStore;
+
+/// Names of the fields appended to every record, in output order
+pub const OUTPUT_FIELDS: [&str; 3] = ["root_domain", "suffix", "error"];
+
+/// Start of the messages of errors about records without a URL
+const MISSING_URL_ERROR: &str = "Missing URL";
+
+/// How far a batch has got, reported after every record
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BatchProgress {
+    /// Records processed so far
+    pub records: u64,
+    /// Bytes of input consumed so far
+    pub bytes_read: u64,
+}
+
+/// Counts of the records processed by a batch
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BatchSummary {
+    /// Number of records processed
+    pub records: u64,
+    /// Number of failed records per error kind (see [`error_kind`])
+    pub errors: BTreeMap<&'static str, u64>,
+}
+
+impl BatchSummary {
+    /// Returns the number of records whose root domain could not be extracted
+    pub fn failed(&self) -> u64 {
+        self.errors.values().sum()
+    }
+
+    /// Returns the number of records with a root domain
+    pub fn succeeded(&self) -> u64 {
+        self.records - self.failed()
+    }
+
+    fn record(&mut self, error: Option<&str>) {
+        self.records += 1;
+        if let Some(error) = error {
+            *self.errors.entry(error_kind(error)).or_default() += 1;
+        }
+    }
+}
+
+impl fmt::Display for BatchSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Processed {} records: {} succeeded, {} failed",
+            self.records,
+            self.succeeded(),
+            self.failed()
+        )?;
+        for (kind, count) in &self.errors {
+            writeln!(f, "  {}: {}", kind, count)?;
+        }
+        Ok(())
+    }
+}
+
+/// Groups an error message into one of a few kinds for the batch summary.
+///
+/// # Arguments
+///
+/// * `message` - An error message of [`SuffixStore::breakdown_url`] or of the batch itself
+///
+/// # Returns
+///
+/// One of `missing URL`, `invalid URL`, `IP address`, `invalid domain` or `no root domain`
+pub fn error_kind(message: &str) -> &'static str {
+    if message.starts_with(MISSING_URL_ERROR) {
+        return "missing URL";
+    }
+    match DomainErrorKind::of(message) {
+        DomainErrorKind::InvalidUrl => "invalid URL",
+        DomainErrorKind::IpAddress => "IP address",
+        DomainErrorKind::InvalidDomain => "invalid domain",
+        DomainErrorKind::NoRootDomain => "no root domain",
+    }
+}
+
+/// Annotates a CSV stream with root domain, suffix and error columns.
+///
+/// The input must have a header row. Every record is written to `output`
+/// unchanged, followed by the [`OUTPUT_FIELDS`] columns. Records are processed
+/// one at a time, so inputs of any size can be streamed.
+///
+/// Records do not need as many fields as the header. Shorter ones are padded
+/// with empty fields, and get a `Missing URL` error when they end before the
+/// URL column; longer ones keep their extra fields before the output columns.
+///
+/// # Arguments
+///
+/// * `store` - The Public Suffix List to extract root domains with
+/// * `input` - The CSV data to read
+/// * `output` - Where to write the annotated CSV
+/// * `column` - Name of the column holding the URL
+/// * `progress` - Called after every record
+///
+/// # Returns
+///
+/// * `Ok(BatchSummary)` - The counts of processed and failed records
+/// * `Err(String)` - An error message if the input is not valid CSV, has no
+///   such column, already has an output column, or writing fails
+pub fn annotate_csv<R, W, P>(
+    store: &SuffixStore,
+    input: R,
+    output: W,
+    column: &str,
+    mut progress: P,
+) -> Result<BatchSummary, String>
+where
+    R: Read,
+    W: Write,
+    P: FnMut(BatchProgress),
+{
+    // Ragged records are read, so each one is reported on its own
+    let mut reader = csv::ReaderBuilder::new().flexible(true).from_reader(input);
+    let mut writer = csv::WriterBuilder::new().flexible(true).from_writer(output);
+
+    let mut headers = reader.byte_headers().map_err(|e| format!("Failed to read CSV: {}", e))?.clone();
+    let index = headers
+        .iter()
+        .position(|name| name == column.as_bytes())
+        .ok_or_else(|| format!("CSV input has no '{}' column", column))?;
+    if let Some(existing) = OUTPUT_FIELDS.iter().find(|field| headers.iter().any(|name| name == field.as_bytes())) {
+        return Err(format!("CSV input already has a '{}' column", existing));
+    }
+    let width = headers.len();
+    headers.extend(OUTPUT_FIELDS);
+    writer.write_byte_record(&headers).map_err(write_error)?;
+
+    let mut summary = BatchSummary::default();
+    let mut record = csv::ByteRecord::new();
+    while reader.read_byte_record(&mut record).map_err(|e| format!("Failed to read CSV: {}", e))? {
+        let url = match record.get(index).map(std::str::from_utf8) {
+            Some(Ok(url)) => Ok(url),
+            Some(Err(_)) => Err("Invalid URL: not valid UTF-8".to_string()),
+            None => Err(format!("{}: record has no such column", MISSING_URL_ERROR)),
+        };
+        let fields = annotate(store, url);
+        summary.record(fields[2].as_deref());
+
+        // Pad short records so the output columns line up with the header
+        while record.len() < width {
+            record.push_field(b"");
+        }
+        for field in &fields {
+            record.push_field(field.as_deref().unwrap_or_default().as_bytes());
+        }
+        writer.write_byte_record(&record).map_err(write_error)?;
+
+        progress(BatchProgress {
+            records: summary.records,
+            bytes_read: reader.position().byte(),
+        });
+    }
+
+    writer.flush().map_err(write_error)?;
+    Ok(summary)
+}
+
+/// Annotates a JSON Lines stream with root domain, suffix and error fields.
+///
+/// Every line must hold a JSON object. The [`OUTPUT_FIELDS`] are appended to
+/// the object as written, so the rest of the line (key order, number
+/// formatting, whitespace) is kept byte for byte. Blank lines are passed
+/// through. Records are processed one at a time, so inputs of any size can be
+/// streamed.
+///
+/// # Arguments
+///
+/// * `store` - The Public Suffix List to extract root domains with
+/// * `input` - The JSON Lines data to read
+/// * `output` - Where to write the annotated JSON Lines
+/// * `pointer` - JSON pointer to the URL in each object, e.g. `/request/url`
+/// * `progress` - Called after every record
+///
+/// # Returns
+///
+/// * `Ok(BatchSummary)` - The counts of processed and failed records
+/// * `Err(String)` - An error message if the pointer is invalid, a line is
+///   not a JSON object or already has an output field, or I/O fails
+pub fn annotate_jsonl<R, W, P>(
+    store: &SuffixStore,
+    mut input: R,
+    output: W,
+    pointer: &str,
+    mut progress: P,
+) -> Result<BatchSummary, String>
+where
+    R: BufRead,
+    W: Write,
+    P: FnMut(BatchProgress),
+{
+    if !pointer.is_empty() && !pointer.starts_with('/') {
+        return Err(format!("Invalid JSON pointer '{}': must be empty or start with '/'", pointer));
+    }
+
+    let mut writer = BufWriter::new(output);
+    let mut summary = BatchSummary::default();
+    let mut bytes_read = 0u64;
+    let mut line = String::new();
+    let mut line_number = 0;
+
+    loop {
+        line.clear();
+        let read = input.read_line(&mut line).map_err(|e| format!("Failed to read input: {}", e))?;
+        if read == 0 {
+            break;
+        }
+        bytes_read += read as u64;
+        line_number += 1;
+
+        let body = line.trim_end();
+        if body.trim_start().is_empty() {
+            writeln!(writer).map_err(write_error)?;
+            continue;
+        }
+
+        let value: Value =
+            serde_json::from_str(body).map_err(|e| format!("line {}: invalid JSON: {}", line_number, e))?;
+        let Some(object) = value.as_object() else {
+            return Err(format!("line {}: expected a JSON object", line_number));
+        };
+        if let Some(existing) = OUTPUT_FIELDS.iter().find(|field| object.contains_key(**field)) {
+            return Err(format!("line {}: input already has a '{}' field", line_number, existing));
+        }
+
+        let url = match value.pointer(pointer) {
+            Some(Value::String(url)) => Ok(url.as_str()),
+            Some(_) => Err(format!("{}: '{}' is not a string", MISSING_URL_ERROR, pointer)),
+            None => Err(format!("{}: no value at '{}'", MISSING_URL_ERROR, pointer)),
+        };
+        let fields = annotate(store, url);
+        summary.record(fields[2].as_deref());
+
+        // A JSON object always ends with '}', so the new fields go right before it
+        let head = &body[..body.len() - 1];
+        let mut separator = if object.is_empty() { "" } else { "," };
+        write!(writer, "{}", head).map_err(write_error)?;
+        for (name, field) in OUTPUT_FIELDS.iter().zip(&fields) {
+            let field = serde_json::to_string(field).map_err(|e| e.to_string())?;
+            write!(writer, "{}\"{}\":{}", separator, name, field).map_err(write_error)?;
+            separator = ",";
+        }
+        writeln!(writer, "}}").map_err(write_error)?;
+
+        progress(BatchProgress {
+            records: summary.records,
+            bytes_read,
+        });
+    }
+
+    writer.flush().map_err(write_error)?;
+    Ok(summary)
+}
+
+/// Computes the [`OUTPUT_FIELDS`] of one record
+fn annotate(store: &SuffixStore, url: Result<&str, String>) -> [Option<String>; 3] {
+    let result = url.and_then(|url| match url.trim() {
+        "" => Err(format!("{}: the value is empty", MISSING_URL_ERROR)),
+        url => store.breakdown_url(url),
+    });
+    match result {
+        Ok(breakdown) => [Some(breakdown.root_domain), Some(breakdown.suffix), None],
+        Err(e) => [None, None, Some(e)],
+    }
+}
+
+fn write_error<E: fmt::Display>(e: E) -> String {
+    format!("Failed to write output: {}", e)
+}
@@ -0,0 +1,63 @@
+use rayon::prelude::*;
+
+use crate::suffix_store::SuffixStore;
+
+impl SuffixStore {
+    /// Extracts the root domains of many domains in parallel.
+    ///
+    /// The domains are spread over the rayon thread pool, all sharing this
+    /// store. Run the call inside [`rayon::ThreadPool::install`] to use a
+    /// pool other than the global one.
+    ///
+    /// # Arguments
+    ///
+    /// * `domains` - A slice, `Vec` or any other iterator of domains
+    ///
+    /// # Returns
+    ///
+    /// One result per input, in input order, as returned by
+    /// [`SuffixStore::extract_root_domain`]. A failing input does not affect
+    /// the others.
+    pub fn extract_root_domains_par<I>(&self, domains: I) -> Vec<Result<String, String>>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str> + Send,
+    {
+        par_map(domains, |domain| self.extract_root_domain(domain))
+    }
+
+    /// Extracts the root domains of many URLs in parallel.
+    ///
+    /// See [`SuffixStore::extract_root_domains_par`] for how the work is
+    /// distributed.
+    ///
+    /// # Arguments
+    ///
+    /// * `urls` - A slice, `Vec` or any other iterator of URLs
+    ///
+    /// # Returns
+    ///
+    /// One result per input, in input order, as returned by
+    /// [`SuffixStore::extract_root_domain_from_url`]. A failing input does not
+    /// affect the others.
+    pub fn extract_root_domains_from_urls_par<I>(&self, urls: I) -> Vec<Result<String, String>>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str> + Send,
+    {
+        par_map(urls, |url| self.extract_root_domain_from_url(url))
+    }
+}
+
+/// Applies `f` to every input on the rayon thread pool, keeping input order
+fn par_map<I, F>(inputs: I, f: F) -> Vec<Result<String, String>>
+where
+    I: IntoIterator,
+    I::Item: AsRef<str> + Send,
+    F: Fn(&str) -> Result<String, String> + Sync,
+{
+    // Collecting first makes any iterator indexable, which keeps the output in
+    // input order; for slices this only copies references
+    let inputs: Vec<I::Item> = inputs.into_iter().collect();
+    inputs.into_par_iter().map(|input| f(input.as_ref())).collect()
+}
@@ -0,0 +1,221 @@
+use crate::domain_parser::LABEL_SEPARATORS;
+
+/// Defanged schemes and the schemes they stand for, matched case-insensitively
+const SCHEMES: &[(&str, &str)] = &[("hxxps", "https"), ("hxxp", "http"), ("fxp", "ftp")];
+
+/// Brackets that defanged characters are wrapped in
+const BRACKETS: &[(char, char)] = &[('[', ']'), ('(', ')'), ('{', '}')];
+
+/// Bracketed spellings and the text they stand for, matched case-insensitively
+const BRACKETED: &[(&str, &str)] = &[
+    (".", "."),
+    ("dot", "."),
+    (":", ":"),
+    ("://", "://"),
+    ("@", "@"),
+    ("at", "@"),
+    ("/", "/"),
+];
+
+/// Longest text between brackets that can be a defanged spelling, e.g. `[ dot ]`
+const MAX_BRACKETED_LEN: usize = 8;
+
+/// Spaces dropped around a bracketed dot, as in `evil [dot] com`
+const DOT_PADDING: [char; 2] = [' ', '\t'];
+
+/// Restores a defanged URL or domain so it can be parsed.
+///
+/// Understands the common conventions of threat intelligence feeds:
+///
+/// - `hxxp`, `hxxps` and `fxp` for the schemes `http`, `https` and `ftp`,
+///   in any case (`hXXps://`)
+/// - `[.]`, `(.)`, `{.}`, `[dot]`, `(dot)` and `\.` for dots, with optional
+///   spaces inside the brackets (`[ . ]`) or around them (`evil [dot] com`)
+/// - `[:]`, `[://]`, `[/]`, `[@]` and `[at]`, in any of the brackets
+///
+/// Everything else is kept as is, so refanging a URL that is not defanged
+/// returns it unchanged.
+///
+/// # Arguments
+///
+/// * `input` - The defanged text, e.g. `hxxps://evil[.]example[.]com`
+///
+/// # Returns
+///
+/// The refanged text, e.g. `https://evil.example.com`
+pub fn refang(input: &str) -> String {
+    refang_pieces(input).into_iter().map(|(_, text)| text).collect()
+}
+
+/// Defangs a URL or domain so it can be shown in reports without being
+/// clickable or resolvable.
+///
+/// Only the scheme and authority are changed: the `http`, `https` and `ftp`
+/// schemes become `hxxp`, `hxxps` and `fxp`, and every dot of the host,
+/// including the full stops IDNA treats as dots (`。`, `．` and `｡`),
+/// becomes `[.]`. A URL starting with `//` or `\\` starts with its host. The
+/// path, query and fragment are kept as is, except for the URLs embedded in
+/// them, as in `?u=https://example.com/`, which are defanged the same way.
+/// A scheme or authority that is already defanged is refanged first, so
+/// defanging twice gives the same result as defanging once.
+///
+/// # Arguments
+///
+/// * `input` - The URL or domain, e.g. `https://evil.example.com/login`
+///
+/// # Returns
+///
+/// The defanged text, e.g. `hxxps://evil[.]example[.]com/login`
+pub fn defang(input: &str) -> String {
+    let mut defanged = String::with_capacity(input.len() + 8);
+    let len = defang_authority(input, &mut defanged);
+    defang_embedded_urls(&input[len..], &mut defanged);
+    defanged
+}
+
+/// Splits text into the pieces [`refang`] produces, each with where it
+/// starts in `input`: a replaced spelling, with the spaces around a
+/// bracketed dot, or a character kept as is
+fn refang_pieces(input: &str) -> Vec<(usize, &str)> {
+    let mut pieces: Vec<(usize, &str)> = Vec::with_capacity(input.len());
+    let mut offset = 0;
+
+    while let Some(c) = input[offset..].chars().next() {
+        let rest = &input[offset..];
+        // Schemes are only recognized at the start of a word, not in `ahxxp:`
+        let word_start = !pieces
+            .last()
+            .and_then(|(_, text)| text.chars().next_back())
+            .is_some_and(|c| c.is_alphanumeric());
+        let scheme = if word_start { defanged_scheme(rest) } else { None };
+        let bracket = bracketed(rest);
+        let replacement = scheme
+            .or(bracket)
+            .or_else(|| rest.starts_with("\\.").then_some((2, ".")));
+
+        match replacement {
+            Some((len, text)) => {
+                let mut start = offset;
+                offset += len;
+                if bracket.is_some_and(|(_, text)| text == ".") {
+                    while let Some(&(padding, _)) = pieces.last().filter(|(_, text)| text.starts_with(DOT_PADDING)) {
+                        start = padding;
+                        pieces.pop();
+                    }
+                    offset = input.len() - input[offset..].trim_start_matches(DOT_PADDING).len();
+                }
+                pieces.push((start, text));
+            }
+            None => {
+                pieces.push((offset, &rest[..c.len_utf8()]));
+                offset += c.len_utf8();
+            }
+        }
+    }
+
+    pieces
+}
+
+/// Defangs the scheme and authority at the start of `url`, refanging them
+/// first, and returns how much of `url` they take up
+fn defang_authority(url: &str, defanged: &mut String) -> usize {
+    let pieces = refang_pieces(url);
+    let mut refanged = String::with_capacity(url.len());
+    let mut starts = Vec::with_capacity(pieces.len());
+    for (_, text) in &pieces {
+        starts.push(refanged.len());
+        refanged.push_str(text);
+    }
+
+    // A piece reaching past the authority, like `[://]` after a host, is
+    // left to the rest
+    let (_, end) = scheme_and_authority(&refanged);
+    let index = (0..pieces.len())
+        .find(|&i| starts[i] + pieces[i].1.len() > end)
+        .unwrap_or(pieces.len());
+    let authority = &refanged[..starts.get(index).copied().unwrap_or(refanged.len())];
+
+    let (scheme, _) = scheme_and_authority(authority);
+    let host_start = match scheme {
+        Some(scheme) => {
+            defanged.push_str(match scheme.to_ascii_lowercase().as_str() {
+                "http" => "hxxp",
+                "https" => "hxxps",
+                "ftp" => "fxp",
+                _ => scheme,
+            });
+            defanged.push(':');
+            scheme.len() + 1
+        }
+        None => 0,
+    };
+    defanged.push_str(&authority[host_start..].replace(LABEL_SEPARATORS, "[.]"));
+
+    pieces.get(index).map_or(url.len(), |&(start, _)| start)
+}
+
+/// Defangs the URLs embedded in the path, query or fragment of a URL, such
+/// as `https://example.com/` in `?u=https://example.com/`, and keeps the rest
+fn defang_embedded_urls(text: &str, defanged: &mut String) {
+    let mut rest = text;
+    while let Some(separator) = rest.find("://") {
+        let scheme_start = rest[..separator].trim_end_matches(|c: char| c.is_ascii_alphanumeric()).len();
+        if is_scheme(&rest[scheme_start..separator]) {
+            defanged.push_str(&rest[..scheme_start]);
+            rest = &rest[scheme_start..];
+            let len = defang_authority(rest, defanged);
+            rest = &rest[len..];
+        } else {
+            defanged.push_str(&rest[..separator + 3]);
+            rest = &rest[separator + 3..];
+        }
+    }
+    defanged.push_str(rest);
+}
+
+/// Finds the scheme and authority at the start of a URL, returning the
+/// scheme, if it is followed by `/` or `\`, and the length of both. A URL
+/// without a scheme, or starting with `//`, starts with its authority.
+fn scheme_and_authority(url: &str) -> (Option<&str>, usize) {
+    let scheme = url
+        .split_once(':')
+        .filter(|(scheme, rest)| is_scheme(scheme) && rest.starts_with(['/', '\\']))
+        .map(|(scheme, _)| scheme);
+    let host_start = scheme.map_or(0, |scheme| scheme.len() + 1);
+    let host = url[host_start..].trim_start_matches(['/', '\\']);
+    let host_start = url.len() - host.len();
+    (scheme, host_start + host.find(['/', '\\', '?', '#']).unwrap_or(host.len()))
+}
+
+/// Matches a defanged scheme followed by its colon, plain or bracketed, at
+/// the start of `text`, returning its length and the real scheme
+fn defanged_scheme(text: &str) -> Option<(usize, &'static str)> {
+    SCHEMES.iter().find_map(|&(defanged, scheme)| {
+        let candidate = text.get(..defanged.len())?;
+        let next = text[defanged.len()..].chars().next();
+        let followed_by_colon = matches!(next, Some(':')) || bracketed(&text[defanged.len()..]).is_some();
+        (candidate.eq_ignore_ascii_case(defanged) && followed_by_colon).then_some((defanged.len(), scheme))
+    })
+}
+
+/// Matches a bracketed spelling at the start of `text`, returning its length
+/// and the text it stands for
+fn bracketed(text: &str) -> Option<(usize, &'static str)> {
+    let open = text.chars().next()?;
+    let &(_, close) = BRACKETS.iter().find(|&&(bracket, _)| bracket == open)?;
+    let inner_len = text[1..].find(close).filter(|&len| len <= MAX_BRACKETED_LEN)?;
+    let inner = text[1..1 + inner_len].trim();
+
+    BRACKETED
+        .iter()
+        .find(|(spelling, _)| inner.eq_ignore_ascii_case(spelling))
+        .map(|&(_, replacement)| (inner_len + 2, replacement))
+}
+
+/// Returns whether `text` is a URL scheme: a letter followed by letters,
+/// digits, `+`, `-` or `.`
+fn is_scheme(text: &str) -> bool {
+    let mut chars = text.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+}
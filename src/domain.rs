@@ -0,0 +1,436 @@
+use serde::de::{self, Deserializer};
+use serde::{Deserialize, Serialize, Serializer};
+use std::cmp::Ordering;
+use std::fmt;
+use std::iter::FusedIterator;
+use std::net::Ipv4Addr;
+use std::str::FromStr;
+
+use crate::domain_parser::{self, INVALID_DOMAIN_ERROR, IPV4_ERROR, NO_ROOT_DOMAIN_ERROR};
+use crate::suffix_store::SuffixStore;
+use crate::suffix_trie::last_labels_start;
+
+/// Maximum length of a domain name in its ASCII form, without the trailing dot
+const MAX_DOMAIN_LEN: usize = 253;
+
+/// Maximum length of a single label
+const MAX_LABEL_LEN: usize = 63;
+
+/// A validated, normalized domain name.
+///
+/// A `Domain` can only be created by parsing, so holding one guarantees that
+/// the name is well-formed. It is stored in its canonical ASCII form:
+/// lowercase, punycode for internationalized labels and without a trailing
+/// dot. Every label is 1 to 63 letters, digits, hyphens or underscores, not
+/// starting or ending with a hyphen, and the whole name is at most 253
+/// characters. IPv4 addresses are rejected.
+///
+/// Domains are ordered by their labels from right to left, so subdomains
+/// sort right after their parent: `example.com < a.example.com < example.org`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Domain(String);
+
+/// A validated root domain (eTLD+1), such as `example.co.uk`.
+///
+/// A `RootDomain` is a [`Domain`] that is its own registrable domain according
+/// to the Public Suffix List.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RootDomain(Domain);
+
+impl Domain {
+    /// Parses and normalizes a domain name.
+    ///
+    /// # Arguments
+    ///
+    /// * `domain` - The domain to parse, e.g. `WWW.Example.com.` or `münchen.de`
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Domain)` - The normalized domain, e.g. `www.example.com`
+    /// * `Err(String)` - An error message if the domain is not valid
+    pub fn parse(domain: &str) -> Result<Self, String> {
+        let invalid = |reason: &str| format!("{} '{}': {}", INVALID_DOMAIN_ERROR, domain, reason);
+
+        let trimmed = domain.strip_suffix('.').unwrap_or(domain);
+        if trimmed.is_empty() {
+            return Err(invalid("it is empty"));
+        }
+        if let Some(issue) = domain_parser::check_punycode(trimmed).first() {
+            return Err(invalid(&issue.to_string()));
+        }
+        let ascii = idna::domain_to_ascii(trimmed).map_err(|_| invalid("it is not a valid internationalized domain name"))?;
+
+        if ascii.len() > MAX_DOMAIN_LEN {
+            return Err(invalid("it is longer than 253 characters"));
+        }
+        if ascii.parse::<Ipv4Addr>().is_ok() {
+            return Err(IPV4_ERROR.to_string());
+        }
+        for label in ascii.split('.') {
+            if label.is_empty() {
+                return Err(invalid("it has an empty label"));
+            }
+            if label.len() > MAX_LABEL_LEN {
+                return Err(invalid(&format!("label '{}' is longer than 63 characters", label)));
+            }
+            if let Some(c) = label.chars().find(|&c| !(c.is_ascii_alphanumeric() || c == '-' || c == '_')) {
+                return Err(invalid(&format!("label '{}' contains '{}'", label, c)));
+            }
+            if label.starts_with('-') || label.ends_with('-') {
+                return Err(invalid(&format!("label '{}' starts or ends with a hyphen", label)));
+            }
+        }
+
+        Ok(Domain(ascii))
+    }
+
+    /// Parses a URL and returns its host as a domain.
+    ///
+    /// See [`domain_parser::parse_url`] for the URL forms accepted.
+    ///
+    /// # Arguments
+    ///
+    /// * `url_str` - A string slice containing the URL to parse
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Domain)` - The host of the URL
+    /// * `Err(String)` - An error message if the URL is invalid or its host is an IP address
+    pub fn from_url(url_str: &str) -> Result<Self, String> {
+        Self::parse(&domain_parser::parse_url(url_str)?)
+    }
+
+    /// Returns the domain in its canonical ASCII form
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Returns the domain with punycode labels converted back to Unicode
+    pub fn to_unicode(&self) -> String {
+        idna::domain_to_unicode(&self.0).0
+    }
+
+    /// Returns the root domain (eTLD+1) of this domain using the default list.
+    ///
+    /// The Public Suffix List is loaded once per process, see [`SuffixStore::global`].
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(RootDomain)` - The root domain, e.g. `example.co.uk` for `www.example.co.uk`
+    /// * `Err(String)` - An error message if the domain is itself a public suffix
+    pub fn root_domain(&self) -> Result<RootDomain, String> {
+        self.root_domain_in(SuffixStore::global()?)
+    }
+
+    /// Returns the root domain (eTLD+1) of this domain using the given list.
+    ///
+    /// # Arguments
+    ///
+    /// * `store` - The Public Suffix List to use
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(RootDomain)` - The root domain, e.g. `example.co.uk` for `www.example.co.uk`
+    /// * `Err(String)` - An error message if the domain is itself a public suffix
+    pub fn root_domain_in(&self, store: &SuffixStore) -> Result<RootDomain, String> {
+        match store.trie().registrable_domain(&self.0) {
+            Some(root) => Ok(RootDomain(Domain(root.to_string()))),
+            None => Err(format!("{} from '{}'", NO_ROOT_DOMAIN_ERROR, self.0)),
+        }
+    }
+
+    /// Returns true if this domain is `other` or one of its subdomains
+    pub fn is_subdomain_of(&self, other: &Domain) -> bool {
+        match self.0.strip_suffix(other.as_str()) {
+            Some("") => true,
+            Some(prefix) => prefix.ends_with('.'),
+            None => false,
+        }
+    }
+
+    /// Returns an iterator over the labels of the domain, from left to right.
+    ///
+    /// The iterator is double-ended, so `labels().rev()` walks from the
+    /// top-level domain down.
+    pub fn labels(&self) -> Labels<'_> {
+        Labels(self.0.split('.'))
+    }
+
+    /// Returns the number of labels in the domain
+    pub fn label_count(&self) -> usize {
+        self.0.matches('.').count() + 1
+    }
+
+    /// Walks from this domain up to its registrable domain using the default list.
+    ///
+    /// The Public Suffix List is loaded once per process, see [`SuffixStore::global`].
+    /// See [`Domain::ancestors_in`] for the domains visited.
+    pub fn ancestors(&self) -> Result<Ancestors<'_>, String> {
+        Ok(self.ancestors_in(SuffixStore::global()?))
+    }
+
+    /// Walks from this domain up to its registrable domain using the given list.
+    ///
+    /// For `a.b.example.co.uk` the iterator yields `a.b.example.co.uk`,
+    /// `b.example.co.uk` and `example.co.uk`, as slices of this domain. Call
+    /// [`Ancestors::through_public_suffix`] to continue up to `co.uk`. A domain
+    /// that is itself a public suffix has no ancestors below the suffix.
+    ///
+    /// # Arguments
+    ///
+    /// * `store` - The Public Suffix List that decides where the registrable domain starts
+    pub fn ancestors_in(&self, store: &SuffixStore) -> Ancestors<'_> {
+        let suffix_labels = store.trie().find(&self.0).map_or(1, |found| found.labels);
+        let suffix_start = last_labels_start(&self.0, suffix_labels).unwrap_or(0);
+        let registrable_start = last_labels_start(&self.0, suffix_labels + 1);
+
+        Ancestors {
+            domain: &self.0,
+            next: Some(0),
+            registrable_start,
+            suffix_start,
+            through_suffix: false,
+        }
+    }
+}
+
+impl RootDomain {
+    /// Parses a root domain, checking it against the default list.
+    ///
+    /// The Public Suffix List is loaded once per process, see [`SuffixStore::global`].
+    ///
+    /// # Arguments
+    ///
+    /// * `domain` - The domain to parse, e.g. `example.co.uk`
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(RootDomain)` - The normalized root domain
+    /// * `Err(String)` - An error message if the domain is invalid or is not a
+    ///   root domain (e.g. `www.example.com` or `co.uk`)
+    pub fn parse(domain: &str) -> Result<Self, String> {
+        Self::parse_in(domain, SuffixStore::global()?)
+    }
+
+    /// Parses a root domain, checking it against the given list.
+    ///
+    /// # Arguments
+    ///
+    /// * `domain` - The domain to parse, e.g. `example.co.uk`
+    /// * `store` - The Public Suffix List to use
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(RootDomain)` - The normalized root domain
+    /// * `Err(String)` - An error message if the domain is invalid or is not a root domain
+    pub fn parse_in(domain: &str, store: &SuffixStore) -> Result<Self, String> {
+        let parsed = Domain::parse(domain)?;
+        let root = parsed.root_domain_in(store)?;
+        if root.0 != parsed {
+            return Err(format!(
+                "'{}' is not a root domain (its root domain is '{}')",
+                parsed, root
+            ));
+        }
+        Ok(root)
+    }
+
+    /// Returns the root domain in its canonical ASCII form
+    pub fn as_str(&self) -> &str {
+        self.0.as_str()
+    }
+
+    /// Returns the root domain as a plain [`Domain`]
+    pub fn as_domain(&self) -> &Domain {
+        &self.0
+    }
+
+    /// Converts the root domain into a plain [`Domain`]
+    pub fn into_domain(self) -> Domain {
+        self.0
+    }
+}
+
+/// Iterator over the labels of a [`Domain`], created by [`Domain::labels`]
+#[derive(Debug, Clone)]
+pub struct Labels<'a>(std::str::Split<'a, char>);
+
+impl<'a> Iterator for Labels<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<&'a str> {
+        self.0.next()
+    }
+}
+
+impl DoubleEndedIterator for Labels<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back()
+    }
+}
+
+impl FusedIterator for Labels<'_> {}
+
+/// Iterator over a [`Domain`] and its parent domains, created by [`Domain::ancestors_in`]
+#[derive(Debug, Clone)]
+pub struct Ancestors<'a> {
+    domain: &'a str,
+    /// Start of the next domain to yield, or `None` once the walk is over
+    next: Option<usize>,
+    /// Start of the registrable domain, or `None` if the domain is a public suffix
+    registrable_start: Option<usize>,
+    /// Start of the public suffix
+    suffix_start: usize,
+    /// Whether to walk past the registrable domain up to the public suffix
+    through_suffix: bool,
+}
+
+impl<'a> Ancestors<'a> {
+    /// Continues the walk past the registrable domain, up to and including the public suffix
+    pub fn through_public_suffix(mut self) -> Self {
+        self.through_suffix = true;
+        self
+    }
+
+    /// Returns the registrable domain, or `None` if the domain is itself a public suffix
+    pub fn registrable_domain(&self) -> Option<&'a str> {
+        self.registrable_start.map(|start| &self.domain[start..])
+    }
+
+    /// Returns the public suffix of the domain
+    pub fn public_suffix(&self) -> &'a str {
+        &self.domain[self.suffix_start..]
+    }
+}
+
+impl<'a> Iterator for Ancestors<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<&'a str> {
+        let start = self.next?;
+        let stop = if self.through_suffix { Some(self.suffix_start) } else { self.registrable_start };
+        if stop.is_none_or(|stop| start > stop) {
+            self.next = None;
+            return None;
+        }
+
+        self.next = self.domain[start..].find('.').map(|dot| start + dot + 1);
+        Some(&self.domain[start..])
+    }
+}
+
+impl FusedIterator for Ancestors<'_> {}
+
+impl FromStr for Domain {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl FromStr for RootDomain {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl fmt::Display for Domain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl fmt::Display for RootDomain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl AsRef<str> for Domain {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AsRef<str> for RootDomain {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl AsRef<Domain> for RootDomain {
+    fn as_ref(&self) -> &Domain {
+        &self.0
+    }
+}
+
+impl From<RootDomain> for Domain {
+    fn from(root: RootDomain) -> Self {
+        root.0
+    }
+}
+
+impl From<Domain> for String {
+    fn from(domain: Domain) -> Self {
+        domain.0
+    }
+}
+
+impl From<RootDomain> for String {
+    fn from(root: RootDomain) -> Self {
+        root.into_domain().0
+    }
+}
+
+impl Ord for Domain {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.rsplit('.').cmp(other.0.rsplit('.'))
+    }
+}
+
+impl PartialOrd for Domain {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for RootDomain {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.cmp(&other.0)
+    }
+}
+
+impl PartialOrd for RootDomain {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Serialize for Domain {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+impl Serialize for RootDomain {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Domain {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let domain = String::deserialize(deserializer)?;
+        Domain::parse(&domain).map_err(de::Error::custom)
+    }
+}
+
+impl<'de> Deserialize<'de> for RootDomain {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let domain = String::deserialize(deserializer)?;
+        RootDomain::parse(&domain).map_err(de::Error::custom)
+    }
+}
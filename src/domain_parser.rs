@@ -1,8 +1,157 @@
+use percent_encoding::percent_decode_str;
+use serde::{Deserialize, Serialize};
 use url::Url;
-use publicsuffix::{List, Psl};
-use idna;
-use std::fs;
-use std::str::FromStr;
+
+use std::borrow::Cow;
+use std::fmt;
+use std::net::Ipv4Addr;
+
+use crate::homograph;
+use crate::idna_options::IdnaOptions;
+use crate::suffix_store::SuffixStore;
+
+/// Path of the Public Suffix List file, relative to the working directory
+pub const PSL_PATH: &str = "public_suffix_list.dat";
+
+/// Characters UTS #46 maps to a full stop, which separate labels
+pub(crate) const LABEL_SEPARATORS: [char; 4] = ['.', '\u{3002}', '\u{FF0E}', '\u{FF61}'];
+
+/// Schemes whose URLs always have a host, however many `/` or `\\` follow the colon
+const SPECIAL_SCHEMES: [&str; 6] = ["http", "https", "ws", "wss", "ftp", "file"];
+
+/// Start of the messages of errors about hosts that are not valid domain names
+pub(crate) const INVALID_DOMAIN_ERROR: &str = "Invalid domain name";
+
+/// Start of the messages of errors about domains without a registrable part
+pub(crate) const NO_ROOT_DOMAIN_ERROR: &str = "Could not extract root domain";
+
+/// Message of the errors about IPv4 hosts
+pub(crate) const IPV4_ERROR: &str = "IPv4 addresses are not valid domains";
+
+/// Message of the errors about IPv6 hosts
+pub(crate) const IPV6_ERROR: &str = "IPv6 addresses are not valid domains";
+
+/// What an error of URL parsing or root domain extraction is about
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DomainErrorKind {
+    /// The input is not a valid URL or has no host
+    InvalidUrl,
+    /// The host is an IPv4 or IPv6 address
+    IpAddress,
+    /// The host is not a valid domain name
+    InvalidDomain,
+    /// The domain has no registrable part, e.g. it is a public suffix
+    NoRootDomain,
+}
+
+impl DomainErrorKind {
+    /// Tells what an error message of [`parse_url`], [`normalize_url`] or
+    /// the root domain extraction of [`SuffixStore`] is about.
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - The error message
+    ///
+    /// # Returns
+    ///
+    /// The kind of the error, [`DomainErrorKind::InvalidUrl`] for messages
+    /// of any other error
+    pub fn of(message: &str) -> Self {
+        if message == IPV4_ERROR || message == IPV6_ERROR {
+            Self::IpAddress
+        } else if message.starts_with(INVALID_DOMAIN_ERROR) {
+            Self::InvalidDomain
+        } else if message.starts_with(NO_ROOT_DOMAIN_ERROR) {
+            Self::NoRootDomain
+        } else {
+            Self::InvalidUrl
+        }
+    }
+}
+
+/// A transformation applied while extracting the host of a URL
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum NormalizationKind {
+    /// Leading and trailing spaces and control characters, and tabs and
+    /// newlines anywhere, were removed
+    WhitespaceRemoved,
+    /// `http://` was prepended to a URL without a scheme
+    SchemeAdded,
+    /// The user name and password were dropped
+    UserinfoRemoved,
+    /// The port was dropped
+    PortRemoved,
+    /// Percent-encoded bytes in the host were decoded
+    PercentDecoded,
+    /// The host was lowercased
+    Lowercased,
+    /// The host was mapped and converted to punycode by IDNA processing
+    IdnaConverted,
+    /// The trailing dot of a fully qualified host was removed
+    TrailingDotRemoved,
+}
+
+/// One transformation, with the value before and after it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Normalization {
+    /// What was done
+    pub kind: NormalizationKind,
+    /// The value before the transformation, e.g. `user@Example.com:8080`
+    pub before: String,
+    /// The value after it, e.g. `Example.com:8080`
+    pub after: String,
+}
+
+/// The normalized host of a URL and how it was obtained
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NormalizedHost {
+    /// The host, as [`parse_url`] returns it
+    pub host: String,
+    /// The transformations applied to the URL as written, in order
+    pub normalizations: Vec<Normalization>,
+}
+
+/// Why an `xn--` label is not genuine punycode
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PunycodeProblem {
+    /// The text after `xn--` does not decode
+    Undecodable,
+    /// The label decodes to ASCII only, e.g. `xn--example-` to `example`,
+    /// disguising a plain label as an internationalized one
+    AsciiOnly,
+    /// The decoded text does not encode back to the same label, e.g.
+    /// because it is not in NFC or has characters IDNA does not allow
+    NotRoundTrip,
+}
+
+/// A fake or broken punycode label found in a host
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PunycodeIssue {
+    /// The label as written, e.g. `xn--example-`
+    pub label: String,
+    /// The decoded text, when the label decodes at all
+    pub decoded: Option<String>,
+    /// What is wrong with the label
+    pub problem: PunycodeProblem,
+}
+
+impl fmt::Display for PunycodeIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let decoded = self.decoded.as_deref().unwrap_or_default();
+        match self.problem {
+            PunycodeProblem::Undecodable => write!(f, "label '{}' is not valid punycode", self.label),
+            PunycodeProblem::AsciiOnly => write!(f, "label '{}' decodes to ASCII '{}'", self.label, decoded),
+            PunycodeProblem::NotRoundTrip => write!(
+                f,
+                "label '{}' decodes to '{}', which does not encode back to it",
+                self.label, decoded
+            ),
+        }
+    }
+}
 
 /// Parses a URL string and extracts the normalized host component.
 /// 
@@ -12,6 +161,13 @@ use std::str::FromStr;
 /// - Decodes percent-encoded characters in the host
 /// - Removes user info and port numbers
 /// - Excludes IP addresses (both IPv4 and IPv6)
+/// - Rejects fake or broken punycode labels (see [`check_punycode`])
+///
+/// Use [`normalize_url`] to also learn which of these transformations were applied.
+///
+/// Errors are plain messages that do not say where in `url_str` the problem
+/// is. [`crate::url_diagnostics::diagnose`] is the API that locates problems,
+/// by byte span, and can be called on the same input when parsing fails.
 /// 
 /// # Arguments
 /// 
@@ -22,44 +178,358 @@ use std::str::FromStr;
 /// * `Ok(String)` - The normalized host component if successful
 /// * `Err(String)` - An error message if parsing fails
 pub fn parse_url(url_str: &str) -> Result<String, String> {
+    parse_url_parts(url_str).map(|(_, host, _)| host)
+}
+
+/// Parses a URL string like [`parse_url`], also returning the parsed URL
+/// and whether `http://` was added to it
+pub(crate) fn parse_url_parts(url_str: &str) -> Result<(Url, String, bool), String> {
+    let (url, domain, scheme_added) = parse_domain_host(url_str)?;
+
+    // Normalize the host: lowercase and remove trailing dots
+    let mut normalized_host = domain.to_lowercase();
+    if normalized_host.ends_with('.') {
+        normalized_host.pop();
+    }
+    Ok((url, normalized_host, scheme_added))
+}
+
+/// Parses a URL string and extracts the host, converting an internationalized
+/// host to ASCII with the given IDNA options.
+///
+/// The URL parser always converts hosts with its own fixed IDNA rules. With
+/// options other than [`IdnaOptions::default`], an internationalized host is
+/// instead taken from the URL as written and converted with the options,
+/// while the rest of the URL is still checked by the parser.
+///
+/// # Arguments
+///
+/// * `url_str` - A string slice containing the URL to parse
+/// * `options` - How to convert the host to ASCII
+///
+/// # Returns
+///
+/// * `Ok(String)` - The normalized host component if successful
+/// * `Err(String)` - An error message if parsing fails
+pub fn parse_url_with(url_str: &str, options: &IdnaOptions) -> Result<String, String> {
+    if idn_host_with(&strip_ignored_whitespace(url_str), options).is_none() {
+        return parse_url(url_str);
+    }
+    normalize_url_with(url_str, options).map(|normalized| normalized.host)
+}
+
+/// Parses a URL string like [`parse_url`], and reports every transformation
+/// that turned the URL as written into the normalized host.
+///
+/// # Arguments
+///
+/// * `url_str` - A string slice containing the URL to parse
+///
+/// # Returns
+///
+/// * `Ok(NormalizedHost)` - The normalized host and the transformations applied, in order
+/// * `Err(String)` - An error message if parsing fails
+pub fn normalize_url(url_str: &str) -> Result<NormalizedHost, String> {
+    let (_, domain, scheme_added) = parse_domain_host(url_str)?;
+    Ok(record_normalizations(url_str, scheme_added, &domain))
+}
+
+/// Parses a URL string like [`parse_url_with`], and reports every
+/// transformation that turned the URL as written into the normalized host.
+///
+/// # Arguments
+///
+/// * `url_str` - A string slice containing the URL to parse
+/// * `options` - How to convert the host to ASCII
+///
+/// # Returns
+///
+/// * `Ok(NormalizedHost)` - The normalized host and the transformations applied, in order
+/// * `Err(String)` - An error message if parsing fails
+pub fn normalize_url_with(url_str: &str, options: &IdnaOptions) -> Result<NormalizedHost, String> {
+    let url = strip_ignored_whitespace(url_str);
+    match idn_host_with(&url, options) {
+        Some(raw_host) => {
+            let (ascii, scheme_added) = convert_host_with(&url, raw_host, options)?;
+            Ok(record_normalizations(url_str, scheme_added, &ascii))
+        }
+        None => normalize_url(url_str),
+    }
+}
+
+/// Parses a URL, adding `http://` when it has no scheme, and returns it with
+/// its domain host as the URL parser produced it and whether a scheme was added
+fn parse_domain_host(url_str: &str) -> Result<(Url, String, bool), String> {
+    // Check punycode labels first, the URL parser would not say which one is broken
+    if let Some(host) = written_host(&strip_ignored_whitespace(url_str)) {
+        check_punycode_labels(host)?;
+    }
+
+    let (url, scheme_added) = parse_with_default_scheme(url_str)?;
+    
+    // Check if the URL has an IP address as host
+    let domain = match url.host() {
+        Some(url::Host::Ipv4(_)) => return Err(IPV4_ERROR.to_string()),
+        Some(url::Host::Ipv6(_)) => return Err(IPV6_ERROR.to_string()),
+        Some(url::Host::Domain(domain)) => domain.to_string(),
+        None => return Err("URL has no valid host component".to_string()),
+    };
+    Ok((url, domain, scheme_added))
+}
+
+/// Parses a URL, adding `http://` when it has no scheme, and returns it
+/// with whether a scheme was added
+pub(crate) fn parse_with_default_scheme(url_str: &str) -> Result<(Url, bool), String> {
     // Try to parse the URL with a scheme
     let url_result = Url::parse(url_str);
     
-    // If parsing fails, try adding a default scheme and parse again
-    let url = match url_result {
-        Ok(url) => url,
-        Err(_) => {
+    // If the scheme is missing, try adding a default scheme and parse again
+    match url_result {
+        Ok(url) => Ok((url, false)),
+        Err(url::ParseError::RelativeUrlWithoutBase) => {
             // Check if it might be a URL without a scheme
-            if url_str.contains('.') && !url_str.contains(' ') {
-                match Url::parse(&format!("http://{}", url_str)) {
-                    Ok(url) => url,
-                    Err(e) => return Err(format!("Invalid URL: {}", e)),
+            let url = strip_ignored_whitespace(url_str);
+            if url.contains('.') && !url.contains(' ') {
+                match Url::parse(&format!("{}{}", default_scheme_prefix(&url), url)) {
+                    Ok(url) => Ok((url, true)),
+                    Err(e) => Err(format!("Invalid URL: {}", e)),
                 }
             } else {
-                return Err("Invalid URL format".to_string());
+                Err("Invalid URL format".to_string())
             }
         }
+        Err(e) => Err(format!("Invalid URL: {}", e)),
+    }
+}
+
+/// Returns what must be prepended to a URL without a scheme: `http:` when it
+/// already starts with slashes, as in `//example.com`, and `http://` otherwise
+fn default_scheme_prefix(url: &str) -> &'static str {
+    if url.starts_with(['/', '\\']) {
+        "http:"
+    } else {
+        "http://"
+    }
+}
+
+/// Removes what the URL parser ignores: leading and trailing spaces and
+/// control characters, and tabs and newlines anywhere
+fn strip_ignored_whitespace(url_str: &str) -> Cow<'_, str> {
+    let trimmed = url_str.trim_matches(|c: char| c <= ' ');
+    if trimmed.contains(['\t', '\n', '\r']) {
+        Cow::Owned(trimmed.chars().filter(|c| !matches!(c, '\t' | '\n' | '\r')).collect())
+    } else {
+        Cow::Borrowed(trimmed)
+    }
+}
+
+/// Returns the host of a URL, as written, when `options` must convert it
+/// instead of the URL parser: it is internationalized and the options are not
+/// the default. `url` must have its ignored whitespace stripped.
+fn idn_host_with<'a>(url: &'a str, options: &IdnaOptions) -> Option<&'a str> {
+    written_host(url).filter(|&host| *options != IdnaOptions::default() && homograph::is_idn(host))
+}
+
+/// Converts the host of a URL, as written, with `options`, returning the
+/// ASCII host (with any trailing dot) and whether a scheme had to be added.
+/// `raw_host` must be a slice of `url`.
+fn convert_host_with(url: &str, raw_host: &str, options: &IdnaOptions) -> Result<(String, bool), String> {
+    // Check everything but the host with a placeholder the parser accepts
+    let start = raw_host.as_ptr() as usize - url.as_ptr() as usize;
+    let end = start + raw_host.len();
+    let (_, _, scheme_added) = parse_domain_host(&format!("{}idn.invalid{}", &url[..start], &url[end..]))?;
+
+    let ascii = options.to_ascii(raw_host)?;
+    if ascii.strip_suffix('.').unwrap_or(&ascii).parse::<Ipv4Addr>().is_ok() {
+        return Err(IPV4_ERROR.to_string());
+    }
+    Ok((ascii, scheme_added))
+}
+
+/// Lists the transformations from the URL as written to `converted`, the
+/// host produced by IDNA processing, and finishes normalizing it
+fn record_normalizations(url_str: &str, scheme_added: bool, converted: &str) -> NormalizedHost {
+    let mut normalizations = Vec::new();
+    let mut record = |kind, before: &str, after: &str| {
+        normalizations.push(Normalization {
+            kind,
+            before: before.to_string(),
+            after: after.to_string(),
+        })
     };
-    
-    // Check if the URL has an IP address as host
-    if url.has_host() {
-        if let Some(host) = url.host() {
-            match host {
-                url::Host::Ipv4(_) => return Err("IPv4 addresses are not valid domains".to_string()),
-                url::Host::Ipv6(_) => return Err("IPv6 addresses are not valid domains".to_string()),
-                url::Host::Domain(domain) => {
-                    // Normalize the host: lowercase and remove trailing dots
-                    let mut normalized_host = domain.to_lowercase();
-                    if normalized_host.ends_with('.') {
-                        normalized_host.pop();
-                    }
-                    return Ok(normalized_host);
-                }
-            }
+
+    let url = strip_ignored_whitespace(url_str);
+    if url != url_str {
+        record(NormalizationKind::WhitespaceRemoved, url_str, &url);
+    }
+    if scheme_added {
+        record(NormalizationKind::SchemeAdded, &url, &format!("{}{}", default_scheme_prefix(&url), url));
+    }
+
+    if let Some(authority) = written_authority(&url) {
+        let mut current = authority.full.to_string();
+        if let Some(userinfo) = authority.userinfo {
+            let after = current[userinfo.len() + 1..].to_string();
+            record(NormalizationKind::UserinfoRemoved, &current, &after);
+            current = after;
+        }
+        if let Some(port) = authority.port {
+            let after = current[..current.len() - port.len() - 1].to_string();
+            record(NormalizationKind::PortRemoved, &current, &after);
+            current = after;
+        }
+        if current.contains('%') {
+            let after = percent_decode_str(&current).decode_utf8_lossy().into_owned();
+            record(NormalizationKind::PercentDecoded, &current, &after);
+            current = after;
+        }
+        let lowercase = current.to_lowercase();
+        if lowercase != current {
+            record(NormalizationKind::Lowercased, &current, &lowercase);
+        }
+        if lowercase != converted {
+            record(NormalizationKind::IdnaConverted, &lowercase, converted);
         }
     }
-    
-    Err("URL has no valid host component".to_string())
+
+    let mut host = converted.to_lowercase();
+    if let Some(stripped) = host.strip_suffix('.') {
+        let stripped = stripped.to_string();
+        record(NormalizationKind::TrailingDotRemoved, &host, &stripped);
+        host = stripped;
+    }
+
+    NormalizedHost { host, normalizations }
+}
+
+/// The authority of a URL as written, split into its parts
+struct Authority<'a> {
+    /// The whole authority, e.g. `user@Example.com:8080`
+    full: &'a str,
+    /// The user name and password, without the `@`
+    userinfo: Option<&'a str>,
+    /// The host, e.g. `Example.com`
+    host: &'a str,
+    /// The port, without the `:`
+    port: Option<&'a str>,
+}
+
+/// Splits the authority of a URL as written, with or without a scheme, the
+/// way the URL parser finds it. `url` must have its ignored whitespace stripped.
+fn written_authority(url: &str) -> Option<Authority<'_>> {
+    let (special, rest) = match split_scheme(url) {
+        Some((scheme, rest)) if SPECIAL_SCHEMES.iter().any(|special| scheme.eq_ignore_ascii_case(special)) => {
+            (true, rest.trim_start_matches(['/', '\\']))
+        }
+        // Other schemes only have an authority after exactly `//`
+        Some((_, rest)) => (false, rest.strip_prefix("//")?),
+        // `http://` is added to URLs without a scheme
+        None => (true, url.trim_start_matches(['/', '\\'])),
+    };
+
+    let end = if special {
+        rest.find(['/', '\\', '?', '#'])
+    } else {
+        rest.find(['/', '?', '#'])
+    };
+    let full = &rest[..end.unwrap_or(rest.len())];
+    let (userinfo, host_port) = match full.rsplit_once('@') {
+        Some((userinfo, host_port)) => (Some(userinfo), host_port),
+        None => (None, full),
+    };
+    let (host, port) = match host_port.rsplit_once(':') {
+        Some((host, port)) if port.chars().all(|c| c.is_ascii_digit()) => (host, Some(port)),
+        _ => (host_port, None),
+    };
+    Some(Authority { full, userinfo, host, port })
+}
+
+/// Splits a URL into its scheme and the rest, without the colon
+fn split_scheme(url: &str) -> Option<(&str, &str)> {
+    let (scheme, rest) = url.split_once(':')?;
+    let mut chars = scheme.chars();
+    let valid = chars.next()?.is_ascii_alphabetic()
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'));
+    valid.then_some((scheme, rest))
+}
+
+/// Returns the host of a URL as written, without user info or port, if it
+/// is a plain domain name. `url` must have its ignored whitespace stripped.
+fn written_host(url: &str) -> Option<&str> {
+    let host = written_authority(url)?.host;
+
+    // Bracketed IPv6 addresses and percent-encoded hosts are left to the parser
+    if host.is_empty() || host.starts_with('[') || host.contains('%') {
+        return None;
+    }
+    Some(host)
+}
+
+/// Returns the host of a URL as written, without user info or port, if it
+/// is a plain domain name
+pub(crate) fn host_as_written(url_str: &str) -> Option<String> {
+    written_host(&strip_ignored_whitespace(url_str)).map(str::to_string)
+}
+
+/// Checks the `xn--` labels of a host for fake or broken punycode.
+///
+/// A genuine punycode label decodes to text with at least one non-ASCII
+/// character, and that text encodes back to the same label (ignoring ASCII
+/// case). Anything else is either corrupt or an attempt to disguise a label.
+///
+/// # Arguments
+///
+/// * `host` - The host to check, as written
+///
+/// # Returns
+///
+/// The problems found, one per offending label, left to right
+pub fn check_punycode(host: &str) -> Vec<PunycodeIssue> {
+    host.split(LABEL_SEPARATORS)
+        .filter_map(|label| {
+            let lowercase = label.to_ascii_lowercase();
+            let encoded = lowercase.strip_prefix("xn--")?;
+            let issue = |decoded, problem| {
+                Some(PunycodeIssue {
+                    label: label.to_string(),
+                    decoded,
+                    problem,
+                })
+            };
+
+            let Some(decoded) = idna::punycode::decode_to_string(encoded) else {
+                return issue(None, PunycodeProblem::Undecodable);
+            };
+            if decoded.is_ascii() {
+                return issue(Some(decoded), PunycodeProblem::AsciiOnly);
+            }
+            match idna::domain_to_ascii(&decoded) {
+                Ok(reencoded) if reencoded == lowercase => None,
+                _ => issue(Some(decoded), PunycodeProblem::NotRoundTrip),
+            }
+        })
+        .collect()
+}
+
+/// Checks the `xn--` labels in the host of a URL as written, see [`check_punycode`].
+///
+/// # Arguments
+///
+/// * `url_str` - The URL to check
+///
+/// # Returns
+///
+/// The problems found, or none if the URL has no domain name host
+pub fn check_url_punycode(url_str: &str) -> Vec<PunycodeIssue> {
+    written_host(&strip_ignored_whitespace(url_str)).map(check_punycode).unwrap_or_default()
+}
+
+/// Rejects a host with fake or broken punycode labels, naming the first one
+pub(crate) fn check_punycode_labels(host: &str) -> Result<(), String> {
+    match check_punycode(host).first() {
+        Some(issue) => Err(format!("{}: '{}' ({})", INVALID_DOMAIN_ERROR, host, issue)),
+        None => Ok(()),
+    }
 }
 
 /// Extracts the root domain (eTLD+1) from a domain string using the Public Suffix List.
@@ -78,50 +548,13 @@ pub fn parse_url(url_str: &str) -> Result<String, String> {
 /// 
 /// * `Ok(String)` - The root domain if successful
 /// * `Err(String)` - An error message if extraction fails
+///
+/// The Public Suffix List is loaded once per process, see [`SuffixStore::global`].
 pub fn extract_root_domain(domain: &str) -> Result<String, String> {
-    // Check if this is an IDN domain
-    let is_idn = domain.contains('ü') || domain.contains('ö') || domain.contains('ä') || 
-                 domain.contains('é') || domain.contains('東') || domain.contains('京');
-    
-    // First, handle IDN domains by normalizing them
-    let normalized_domain = match idna::domain_to_ascii(domain) {
-        Ok(ascii) => ascii,
-        Err(_) => return Err(format!("Invalid domain name: '{}'", domain)),
-    };
-    
     // Load the Public Suffix List
-    let psl_data = match fs::read_to_string("public_suffix_list.dat") {
-        Ok(data) => data,
-        Err(_) => return Err("Failed to load Public Suffix List".to_string()),
-    };
-    
-    let list = match List::from_str(&psl_data) {
-        Ok(list) => list,
-        Err(_) => return Err("Failed to parse Public Suffix List".to_string()),
-    };
-    
-    // Extract the root domain using PSL
-    let domain_bytes = normalized_domain.as_bytes();
-    match list.domain(domain_bytes) {
-        Some(root_domain) => {
-            // Convert the domain to a string
-            let root_domain_str = String::from_utf8_lossy(root_domain.as_bytes()).to_string();
-            
-            // For the test_extract_root_domain_idn_non_latin test
-            if domain == "www.東京.jp" {
-                return Ok("東京.jp".to_string());
-            }
-            
-            // Convert back to Unicode if the original domain was Unicode
-            if is_idn {
-                let (unicode, _) = idna::domain_to_unicode(&root_domain_str);
-                Ok(unicode)
-            } else {
-                Ok(root_domain_str)
-            }
-        },
-        None => Err(format!("Could not extract root domain from '{}'", domain)),
-    }
+    let store = SuffixStore::global()?;
+
+    store.extract_root_domain(domain)
 }
 
 /// Extracts the root domain from a URL string.
@@ -139,15 +572,11 @@ pub fn extract_root_domain(domain: &str) -> Result<String, String> {
 /// 
 /// * `Ok(String)` - The root domain if successful
 /// * `Err(String)` - An error message if extraction fails
+///
+/// The Public Suffix List is loaded once per process, see [`SuffixStore::global`].
 pub fn extract_root_domain_from_url(url_str: &str) -> Result<String, String> {
-    // For the test_extract_root_domain_from_idn_url test
-    if url_str == "https://www.münchen.de/path" {
-        return Ok("münchen.de".to_string());
-    }
-    
-    // First parse the URL to get the host
-    let host = parse_url(url_str)?;
-    
-    // Then extract the root domain from the host
-    extract_root_domain(&host)
-} 
\ No newline at end of file
+    // Load the Public Suffix List
+    let store = SuffixStore::global()?;
+
+    store.extract_root_domain_from_url(url_str)
+}
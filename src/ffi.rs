@@ -0,0 +1,329 @@
+use std::cell::RefCell;
+use std::ffi::{c_char, CStr, CString};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+
+use crate::domain_parser::{self, DomainErrorKind};
+use crate::suffix_store::SuffixStore;
+
+/// The call succeeded
+pub const DV_OK: i32 = 0;
+/// A required pointer argument was NULL
+pub const DV_ERR_NULL_POINTER: i32 = 1;
+/// A string argument was not valid UTF-8
+pub const DV_ERR_INVALID_UTF8: i32 = 2;
+/// The Public Suffix List could not be loaded or parsed
+pub const DV_ERR_LOAD_FAILED: i32 = 3;
+/// The input is not a valid URL or has no host
+pub const DV_ERR_INVALID_URL: i32 = 4;
+/// The URL host is an IPv4 or IPv6 address
+pub const DV_ERR_IP_ADDRESS: i32 = 5;
+/// The input is not a valid domain name
+pub const DV_ERR_INVALID_DOMAIN: i32 = 6;
+/// The domain has no registrable part (e.g. it is a public suffix)
+pub const DV_ERR_NO_ROOT_DOMAIN: i32 = 7;
+/// The caller's buffer is too small; the required length has been reported
+pub const DV_ERR_BUFFER_TOO_SMALL: i32 = 8;
+/// An unexpected internal error occurred
+pub const DV_ERR_INTERNAL: i32 = 9;
+
+/// Opaque handle to a loaded Public Suffix List
+pub struct DvSuffixStore {
+    store: SuffixStore,
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Creates a store from the Public Suffix List embedded in the library.
+///
+/// The returned handle must be released with `dv_store_free`. Returns NULL
+/// only if an internal error occurs.
+#[no_mangle]
+pub extern "C" fn dv_store_new_embedded() -> *mut DvSuffixStore {
+    match panic::catch_unwind(SuffixStore::embedded) {
+        Ok(store) => Box::into_raw(Box::new(DvSuffixStore { store })),
+        Err(_) => {
+            set_last_error("Failed to load the embedded Public Suffix List");
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Loads a store from a `public_suffix_list.dat` file.
+///
+/// On success `*out` receives a handle that must be released with
+/// `dv_store_free`.
+///
+/// # Safety
+///
+/// `path` must be a NUL-terminated string and `out` a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn dv_store_new_from_file(
+    path: *const c_char,
+    out: *mut *mut DvSuffixStore,
+) -> i32 {
+    guard(|| {
+        if out.is_null() {
+            return Err((DV_ERR_NULL_POINTER, "out is NULL".to_string()));
+        }
+        let path = read_str(path)?;
+        let store = SuffixStore::from_file(path).map_err(|e| (DV_ERR_LOAD_FAILED, e))?;
+        *out = Box::into_raw(Box::new(DvSuffixStore { store }));
+        Ok(())
+    })
+}
+
+/// Releases a store. Passing NULL is a no-op.
+///
+/// # Safety
+///
+/// `store` must be NULL or a handle returned by this library that has not
+/// been freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn dv_store_free(store: *mut DvSuffixStore) {
+    if !store.is_null() {
+        drop(Box::from_raw(store));
+    }
+}
+
+/// Extracts the normalized host of a URL into a caller buffer.
+///
+/// The result is written NUL-terminated to `buf`. `*out_len` (if not NULL)
+/// receives the length of the result without the terminator, also when the
+/// buffer is too small, so callers can pass a NULL buffer to query the size.
+///
+/// # Safety
+///
+/// `url` must be a NUL-terminated string, `buf` must be NULL or point to at
+/// least `buf_len` writable bytes, and `out_len` must be NULL or valid.
+#[no_mangle]
+pub unsafe extern "C" fn dv_parse_url(
+    url: *const c_char,
+    buf: *mut c_char,
+    buf_len: usize,
+    out_len: *mut usize,
+) -> i32 {
+    guard(|| {
+        let host = parse_url(read_str(url)?)?;
+        write_buf(&host, buf, buf_len, out_len)
+    })
+}
+
+/// Extracts the root domain (eTLD+1) of a domain into a caller buffer.
+///
+/// See `dv_parse_url` for the buffer contract.
+///
+/// # Safety
+///
+/// `store` must be a valid handle, `domain` a NUL-terminated string, `buf`
+/// NULL or at least `buf_len` writable bytes, and `out_len` NULL or valid.
+#[no_mangle]
+pub unsafe extern "C" fn dv_extract_root_domain(
+    store: *const DvSuffixStore,
+    domain: *const c_char,
+    buf: *mut c_char,
+    buf_len: usize,
+    out_len: *mut usize,
+) -> i32 {
+    guard(|| {
+        let root = extract_root_domain(read_store(store)?, read_str(domain)?)?;
+        write_buf(&root, buf, buf_len, out_len)
+    })
+}
+
+/// Extracts the root domain (eTLD+1) of a URL into a caller buffer.
+///
+/// The root domain is in Unicode when the URL spells its host that way, as
+/// with `SuffixStore::extract_root_domain_from_url`. See `dv_parse_url` for
+/// the buffer contract.
+///
+/// # Safety
+///
+/// `store` must be a valid handle, `url` a NUL-terminated string, `buf`
+/// NULL or at least `buf_len` writable bytes, and `out_len` NULL or valid.
+#[no_mangle]
+pub unsafe extern "C" fn dv_extract_root_domain_from_url(
+    store: *const DvSuffixStore,
+    url: *const c_char,
+    buf: *mut c_char,
+    buf_len: usize,
+    out_len: *mut usize,
+) -> i32 {
+    guard(|| {
+        let root = extract_root_domain_from_url(read_store(store)?, read_str(url)?)?;
+        write_buf(&root, buf, buf_len, out_len)
+    })
+}
+
+/// Extracts the root domain of a domain as a newly allocated string.
+///
+/// On success `*out` receives a NUL-terminated string that must be released
+/// with `dv_string_free`.
+///
+/// # Safety
+///
+/// `store` must be a valid handle, `domain` a NUL-terminated string and
+/// `out` a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn dv_extract_root_domain_owned(
+    store: *const DvSuffixStore,
+    domain: *const c_char,
+    out: *mut *mut c_char,
+) -> i32 {
+    guard(|| {
+        let root = extract_root_domain(read_store(store)?, read_str(domain)?)?;
+        write_owned(root, out)
+    })
+}
+
+/// Extracts the root domain of a URL as a newly allocated string, in
+/// Unicode when the URL spells its host that way.
+///
+/// On success `*out` receives a NUL-terminated string that must be released
+/// with `dv_string_free`.
+///
+/// # Safety
+///
+/// `store` must be a valid handle, `url` a NUL-terminated string and `out`
+/// a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn dv_extract_root_domain_from_url_owned(
+    store: *const DvSuffixStore,
+    url: *const c_char,
+    out: *mut *mut c_char,
+) -> i32 {
+    guard(|| {
+        let root = extract_root_domain_from_url(read_store(store)?, read_str(url)?)?;
+        write_owned(root, out)
+    })
+}
+
+/// Releases a string returned by this library. Passing NULL is a no-op.
+///
+/// # Safety
+///
+/// `s` must be NULL or a string returned by a `*_owned` function that has
+/// not been freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn dv_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+/// Returns the message of the last error on the calling thread, or NULL.
+///
+/// The pointer stays valid until the next call into this library on the
+/// same thread and must not be freed.
+#[no_mangle]
+pub extern "C" fn dv_last_error_message() -> *const c_char {
+    LAST_ERROR.with(|last| match &*last.borrow() {
+        Some(message) => message.as_ptr(),
+        None => ptr::null(),
+    })
+}
+
+/// Returns a static description of a status code. The result must not be freed.
+#[no_mangle]
+pub extern "C" fn dv_status_description(status: i32) -> *const c_char {
+    let description: &'static CStr = match status {
+        DV_OK => c"ok",
+        DV_ERR_NULL_POINTER => c"null pointer argument",
+        DV_ERR_INVALID_UTF8 => c"argument is not valid UTF-8",
+        DV_ERR_LOAD_FAILED => c"failed to load the Public Suffix List",
+        DV_ERR_INVALID_URL => c"invalid URL",
+        DV_ERR_IP_ADDRESS => c"IP addresses are not valid domains",
+        DV_ERR_INVALID_DOMAIN => c"invalid domain name",
+        DV_ERR_NO_ROOT_DOMAIN => c"no root domain",
+        DV_ERR_BUFFER_TOO_SMALL => c"buffer too small",
+        _ => c"internal error",
+    };
+    description.as_ptr()
+}
+
+type FfiResult<T> = Result<T, (i32, String)>;
+
+/// Runs an FFI call body, recording its error and never unwinding into C
+fn guard<F: FnOnce() -> FfiResult<()>>(body: F) -> i32 {
+    let (status, message) = match panic::catch_unwind(AssertUnwindSafe(body)) {
+        Ok(Ok(())) => {
+            LAST_ERROR.with(|last| *last.borrow_mut() = None);
+            return DV_OK;
+        }
+        Ok(Err(error)) => error,
+        Err(_) => (DV_ERR_INTERNAL, "Unexpected internal error".to_string()),
+    };
+    set_last_error(&message);
+    status
+}
+
+fn set_last_error(message: &str) {
+    let message = CString::new(message.replace('\0', " ")).unwrap_or_default();
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(message));
+}
+
+unsafe fn read_str<'a>(s: *const c_char) -> FfiResult<&'a str> {
+    if s.is_null() {
+        return Err((DV_ERR_NULL_POINTER, "string argument is NULL".to_string()));
+    }
+    CStr::from_ptr(s)
+        .to_str()
+        .map_err(|_| (DV_ERR_INVALID_UTF8, "string argument is not valid UTF-8".to_string()))
+}
+
+unsafe fn read_store<'a>(store: *const DvSuffixStore) -> FfiResult<&'a SuffixStore> {
+    match store.as_ref() {
+        Some(handle) => Ok(&handle.store),
+        None => Err((DV_ERR_NULL_POINTER, "store is NULL".to_string())),
+    }
+}
+
+fn parse_url(url: &str) -> FfiResult<String> {
+    domain_parser::parse_url(url).map_err(error_status)
+}
+
+fn extract_root_domain(store: &SuffixStore, domain: &str) -> FfiResult<String> {
+    store.extract_root_domain(domain).map_err(error_status)
+}
+
+fn extract_root_domain_from_url(store: &SuffixStore, url: &str) -> FfiResult<String> {
+    store.extract_root_domain_from_url(url).map_err(error_status)
+}
+
+/// Pairs an error message with the status code of its kind
+fn error_status(e: String) -> (i32, String) {
+    let status = match DomainErrorKind::of(&e) {
+        DomainErrorKind::InvalidUrl => DV_ERR_INVALID_URL,
+        DomainErrorKind::IpAddress => DV_ERR_IP_ADDRESS,
+        DomainErrorKind::InvalidDomain => DV_ERR_INVALID_DOMAIN,
+        DomainErrorKind::NoRootDomain => DV_ERR_NO_ROOT_DOMAIN,
+    };
+    (status, e)
+}
+
+unsafe fn write_buf(value: &str, buf: *mut c_char, buf_len: usize, out_len: *mut usize) -> FfiResult<()> {
+    if !out_len.is_null() {
+        *out_len = value.len();
+    }
+    if buf.is_null() || buf_len <= value.len() {
+        return Err((
+            DV_ERR_BUFFER_TOO_SMALL,
+            format!("buffer of {} bytes is too small, {} bytes are needed", buf_len, value.len() + 1),
+        ));
+    }
+    ptr::copy_nonoverlapping(value.as_ptr(), buf.cast::<u8>(), value.len());
+    *buf.add(value.len()) = 0;
+    Ok(())
+}
+
+unsafe fn write_owned(value: String, out: *mut *mut c_char) -> FfiResult<()> {
+    if out.is_null() {
+        return Err((DV_ERR_NULL_POINTER, "out is NULL".to_string()));
+    }
+    let value = CString::new(value)
+        .map_err(|_| (DV_ERR_INTERNAL, "result contains a NUL byte".to_string()))?;
+    *out = value.into_raw();
+    Ok(())
+}
@@ -0,0 +1,168 @@
+use serde::{Deserialize, Serialize};
+use unicode_script::{Script, UnicodeScript};
+use unicode_security::{is_potential_mixed_script_confusable_char, skeleton, RestrictionLevelDetection};
+
+use crate::idna_options;
+
+/// How likely a host is to be a homograph of another, for phishing filters
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Risk {
+    /// ASCII, or a single script or an accepted CJK combination
+    Low,
+    /// Latin mixed with one other common script, e.g. Latin and Arabic
+    Medium,
+    /// Latin mixed with Cyrillic or Greek, several scripts, characters not
+    /// allowed in identifiers, or a label spelled entirely with look-alikes
+    High,
+}
+
+/// The UTS #39 restriction level a label satisfies, from most to least strict
+///
+/// See <https://www.unicode.org/reports/tr39/#Restriction_Level_Detection>.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RestrictionLevel {
+    /// Only ASCII characters
+    AsciiOnly,
+    /// All characters from one script
+    SingleScript,
+    /// Latin combined with Han and Japanese kana, Han and Bopomofo, or Han and Hangul
+    HighlyRestrictive,
+    /// Latin combined with one other recommended script except Cyrillic and Greek
+    ModeratelyRestrictive,
+    /// Any combination of recommended scripts
+    MinimallyRestrictive,
+    /// Characters outside the identifier profile
+    Unrestricted,
+}
+
+impl From<unicode_security::RestrictionLevel> for RestrictionLevel {
+    fn from(level: unicode_security::RestrictionLevel) -> Self {
+        match level {
+            unicode_security::RestrictionLevel::ASCIIOnly => RestrictionLevel::AsciiOnly,
+            unicode_security::RestrictionLevel::SingleScript => RestrictionLevel::SingleScript,
+            unicode_security::RestrictionLevel::HighlyRestrictive => RestrictionLevel::HighlyRestrictive,
+            unicode_security::RestrictionLevel::ModeratelyRestrictive => RestrictionLevel::ModeratelyRestrictive,
+            unicode_security::RestrictionLevel::MinimallyRestrictive => RestrictionLevel::MinimallyRestrictive,
+            unicode_security::RestrictionLevel::Unrestricted => RestrictionLevel::Unrestricted,
+        }
+    }
+}
+
+/// The UTS #39 checks for one label of a host
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LabelReport {
+    /// The label in Unicode form
+    pub label: String,
+    /// The scripts used by the label, in order of first use, ignoring
+    /// characters common to all scripts such as digits and hyphens
+    pub scripts: Vec<String>,
+    /// The most restrictive level the label satisfies
+    pub restriction_level: RestrictionLevel,
+    /// Whether the label mixes scripts that are not normally written together
+    pub mixed_script: bool,
+    /// Whether the label is written in a single non-Latin script using only
+    /// characters that look like Latin ones, like Cyrillic `аррӏе`
+    pub whole_script_confusable: bool,
+    /// The confusable skeleton of the label; two labels with the same
+    /// skeleton look alike
+    pub skeleton: String,
+    /// The risk of this label
+    pub risk: Risk,
+}
+
+/// The UTS #39 verdict for a host
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HomographReport {
+    /// The host in Unicode form
+    pub host: String,
+    /// The confusable skeleton of the whole host
+    pub skeleton: String,
+    /// The highest risk of any label
+    pub risk: Risk,
+    /// The checks for each label, left to right
+    pub labels: Vec<LabelReport>,
+}
+
+/// Runs the UTS #39 mixed-script and confusable checks on a host.
+///
+/// The host may be given in Unicode or punycode form; punycode labels are
+/// decoded first, so `xn--pple-43d.com` is reported as `аpple.com` (with a
+/// Cyrillic `а`) mixing Cyrillic and Latin.
+///
+/// # Arguments
+///
+/// * `host` - The host to check, e.g. as returned by [`crate::domain_parser::parse_url`]
+///
+/// # Returns
+///
+/// The per-label checks and the overall risk verdict
+pub fn analyze(host: &str) -> HomographReport {
+    let unicode = idna_options::to_unicode(host);
+    let labels: Vec<LabelReport> = unicode.split('.').map(analyze_label).collect();
+
+    HomographReport {
+        skeleton: skeleton(&unicode).collect(),
+        risk: labels.iter().map(|label| label.risk).max().unwrap_or(Risk::Low),
+        host: unicode,
+        labels,
+    }
+}
+
+/// Returns whether a host has a label that is internationalized, in either
+/// Unicode or punycode form
+///
+/// # Arguments
+///
+/// * `host` - The host to check
+pub fn is_idn(host: &str) -> bool {
+    host.split('.')
+        .any(|label| !label.is_ascii() || label.get(..4).is_some_and(|prefix| prefix.eq_ignore_ascii_case("xn--")))
+}
+
+/// Runs the checks on one Unicode label
+fn analyze_label(label: &str) -> LabelReport {
+    let mut scripts: Vec<Script> = Vec::new();
+    for c in label.chars() {
+        let script = c.script();
+        if script != Script::Common && script != Script::Inherited && !scripts.contains(&script) {
+            scripts.push(script);
+        }
+    }
+
+    let restriction_level = RestrictionLevel::from(label.detect_restriction_level());
+    let mixed_script = restriction_level > RestrictionLevel::HighlyRestrictive;
+    let skeleton: String = skeleton(label).collect();
+
+    // UTS #39 calls a single-script label whole-script confusable when the
+    // same skeleton can be spelled in another script; for hostnames the
+    // dangerous case is a non-Latin label that could pass for a Latin one
+    let whole_script_confusable = restriction_level == RestrictionLevel::SingleScript
+        && !scripts.contains(&Script::Latin)
+        && label
+            .chars()
+            .filter(|c| !c.is_ascii())
+            .all(is_potential_mixed_script_confusable_char)
+        && skeleton.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+
+    let risk = if whole_script_confusable {
+        Risk::High
+    } else {
+        match restriction_level {
+            RestrictionLevel::AsciiOnly | RestrictionLevel::SingleScript | RestrictionLevel::HighlyRestrictive => Risk::Low,
+            RestrictionLevel::ModeratelyRestrictive => Risk::Medium,
+            RestrictionLevel::MinimallyRestrictive | RestrictionLevel::Unrestricted => Risk::High,
+        }
+    };
+
+    LabelReport {
+        label: label.to_string(),
+        scripts: scripts.into_iter().map(|script| script.full_name().to_string()).collect(),
+        restriction_level,
+        mixed_script,
+        whole_script_confusable,
+        skeleton,
+        risk,
+    }
+}
@@ -0,0 +1,238 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use unicode_security::GeneralSecurityProfile;
+
+use crate::homograph::{self, RestrictionLevel};
+use crate::idn_tables::{DisallowedCodePoint, IdnTables};
+use crate::idna_options;
+
+/// Top-level domains under which labels written entirely in a script that
+/// imitates Latin are expected, and shown in Unicode. Only used for
+/// top-level domains without a table in [`IdnTables::builtin`].
+const TLD_SCRIPTS: &[(&str, &[&str])] = &[
+    ("Cyrillic", &[
+        "by", "bg", "kg", "kz", "mk", "mn", "rs", "ru", "su", "tj", "ua", "uz",
+        "бг", "бел", "дети", "ею", "католик", "ком", "қаз", "мкд", "мон", "москва", "онлайн",
+        "орг", "рус", "сайт", "срб", "укр",
+    ]),
+    ("Greek", &["cy", "gr", "ελ", "ευ"]),
+];
+
+/// Why a host is displayed in Unicode or punycode form
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum DisplayReason {
+    /// The host has no internationalized labels, so both forms are the same
+    Ascii,
+    /// Every internationalized label passed the checks
+    Allowed,
+    /// A punycode label does not decode to valid Unicode
+    InvalidPunycode { label: String },
+    /// A label contains a character outside the UTS #39 identifier profile,
+    /// such as a symbol or an invisible character
+    DisallowedCharacter { label: String, character: char },
+    /// A label mixes scripts beyond what is moderately restrictive: Latin
+    /// with Cyrillic or Greek, or three or more scripts
+    MixedScript { label: String, scripts: Vec<String> },
+    /// A label is written entirely with look-alikes of Latin letters in a
+    /// script the top-level domain is not meant for
+    WholeScriptConfusable { label: String, script: String, tld: String },
+    /// The registrable label has code points outside the repertoire of its
+    /// top-level domain, see [`IdnTables`]
+    OutsideRepertoire {
+        label: String,
+        tld: String,
+        disallowed: Vec<DisallowedCodePoint>,
+    },
+}
+
+impl fmt::Display for DisplayReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DisplayReason::Ascii => write!(f, "host is ASCII"),
+            DisplayReason::Allowed => write!(f, "all labels pass the display policy"),
+            DisplayReason::InvalidPunycode { label } => write!(f, "label '{}' is not valid punycode", label),
+            DisplayReason::DisallowedCharacter { label, character } => write!(
+                f,
+                "label '{}' contains disallowed character U+{:04X}",
+                label, *character as u32
+            ),
+            DisplayReason::MixedScript { label, scripts } => {
+                write!(f, "label '{}' mixes scripts {}", label, scripts.join(", "))
+            }
+            DisplayReason::WholeScriptConfusable { label, script, tld } => write!(
+                f,
+                "label '{}' is a {} look-alike of a Latin label under '{}'",
+                label, script, tld
+            ),
+            DisplayReason::OutsideRepertoire { label, tld, disallowed } => {
+                let code_points: Vec<&str> = disallowed.iter().map(|c| c.code_point.as_str()).collect();
+                write!(
+                    f,
+                    "label '{}' has code points outside the '{}' repertoire: {}",
+                    label,
+                    tld,
+                    code_points.join(", ")
+                )
+            }
+        }
+    }
+}
+
+/// The form a host should be shown in, and why
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DisplayDecision {
+    /// The host to show: Unicode when allowed, punycode otherwise
+    pub host: String,
+    /// Whether `host` is the Unicode form
+    pub unicode: bool,
+    /// Why this form was chosen
+    pub reason: DisplayReason,
+}
+
+/// Chooses between the Unicode and punycode forms of a host the way browsers do.
+///
+/// The Unicode form is shown only when every internationalized label:
+///
+/// - decodes from punycode,
+/// - uses only characters of the UTS #39 identifier profile,
+/// - is at most moderately restrictive, so Latin may be combined with one
+///   other common script but not with Cyrillic or Greek, and
+/// - is not a whole-script confusable of a Latin label, unless the
+///   top-level domain is meant for that script: its table in
+///   [`IdnTables::builtin`] allows every code point of the label (`рор.рф`
+///   is shown, `аррӏе.рф` is not, as `ӏ` is not a Russian letter), or,
+///   without a table, the script is expected under it (Cyrillic `аррӏе.ru`
+///   is shown, `аррӏе.com` is not).
+///
+/// Otherwise the punycode form is shown, and the first failing check is
+/// recorded as the reason.
+///
+/// # Arguments
+///
+/// * `host` - The host, in Unicode or punycode form
+///
+/// # Returns
+///
+/// The host to display, its form and the reason for it
+pub fn display_host(host: &str) -> DisplayDecision {
+    decide(host, false)
+}
+
+/// Chooses between the Unicode and punycode forms of a root domain.
+///
+/// On top of the checks of [`display_host`], the registrable label (`bаnk`
+/// in `bаnk.de`) must only use code points of its top-level domain's
+/// repertoire, for the top-level domains that have a table in
+/// [`IdnTables::builtin`]: a registry could never have registered it
+/// otherwise.
+///
+/// # Arguments
+///
+/// * `root_domain` - The root domain (eTLD+1), in Unicode or punycode form
+///
+/// # Returns
+///
+/// The root domain to display, its form and the reason for it
+pub fn display_root_domain(root_domain: &str) -> DisplayDecision {
+    decide(root_domain, true)
+}
+
+/// Runs the display checks on a host, and the repertoire check on its first
+/// label if it is a root domain
+fn decide(host: &str, root_domain: bool) -> DisplayDecision {
+    let ascii = match idna::domain_to_ascii(host) {
+        Ok(ascii) => ascii,
+        Err(_) => host.to_string(),
+    };
+    if !homograph::is_idn(&ascii) {
+        return DisplayDecision {
+            host: ascii,
+            unicode: false,
+            reason: DisplayReason::Ascii,
+        };
+    }
+
+    let reason = check_labels(&ascii, root_domain);
+    match reason {
+        DisplayReason::Allowed => DisplayDecision {
+            host: idna_options::to_unicode(&ascii),
+            unicode: true,
+            reason,
+        },
+        reason => DisplayDecision {
+            host: ascii,
+            unicode: false,
+            reason,
+        },
+    }
+}
+
+/// Runs the display checks on the labels of an ASCII host
+fn check_labels(ascii: &str, root_domain: bool) -> DisplayReason {
+    for label in ascii.split('.') {
+        if !label.get(..4).is_some_and(|prefix| prefix.eq_ignore_ascii_case("xn--")) {
+            continue;
+        }
+        let (unicode, result) = idna::domain_to_unicode(label);
+        if result.is_err() || unicode.is_ascii() {
+            return DisplayReason::InvalidPunycode { label: label.to_string() };
+        }
+    }
+
+    let report = homograph::analyze(ascii);
+    let tld = report.labels.last().map(|label| label.label.clone()).unwrap_or_default();
+    let repertoire = ascii.rsplit('.').next().and_then(|tld_ascii| IdnTables::builtin().get(tld_ascii));
+
+    for label in report.labels.iter().filter(|label| !label.label.is_ascii()) {
+        if let Some(character) = label
+            .label
+            .chars()
+            .find(|&c| !c.is_ascii() && !c.identifier_allowed())
+        {
+            return DisplayReason::DisallowedCharacter {
+                label: label.label.clone(),
+                character,
+            };
+        }
+
+        if label.restriction_level > RestrictionLevel::ModeratelyRestrictive {
+            return DisplayReason::MixedScript {
+                label: label.label.clone(),
+                scripts: label.scripts.clone(),
+            };
+        }
+
+        if label.whole_script_confusable {
+            let script = label.scripts.first().cloned().unwrap_or_default();
+            let allowed = match repertoire {
+                Some(repertoire) => repertoire.disallowed(&label.label).is_empty(),
+                None => TLD_SCRIPTS
+                    .iter()
+                    .any(|(name, tlds)| *name == script && tlds.contains(&tld.as_str())),
+            };
+            if !allowed {
+                return DisplayReason::WholeScriptConfusable {
+                    label: label.label.clone(),
+                    script,
+                    tld,
+                };
+            }
+        }
+    }
+
+    if root_domain {
+        if let (Some(label), Some(repertoire)) = (report.labels.first(), repertoire) {
+            let disallowed = repertoire.disallowed(&label.label);
+            if !disallowed.is_empty() {
+                return DisplayReason::OutsideRepertoire {
+                    label: label.label.clone(),
+                    tld,
+                    disallowed,
+                };
+            }
+        }
+    }
+
+    DisplayReason::Allowed
+}
@@ -0,0 +1,221 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
+
+use crate::domain::Domain;
+use crate::suffix_store::SuffixStore;
+
+/// The repertoire tables bundled into the binary, keyed by ASCII TLD
+const BUILTIN_TABLES: &[(&str, &str)] = &[
+    ("at", include_str!("../idn_tables/at.txt")),
+    ("ch", include_str!("../idn_tables/ch.txt")),
+    ("de", include_str!("../idn_tables/de.txt")),
+    ("dk", include_str!("../idn_tables/dk.txt")),
+    ("fr", include_str!("../idn_tables/fr.txt")),
+    ("jp", include_str!("../idn_tables/jp.txt")),
+    ("li", include_str!("../idn_tables/ch.txt")),
+    ("xn--p1ai", include_str!("../idn_tables/xn--p1ai.txt")),
+];
+
+/// The bundled tables, parsed on first use
+static BUILTIN: OnceLock<IdnTables> = OnceLock::new();
+
+/// The code points a registry allows in the labels it registers
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Repertoire {
+    /// Sorted, non-overlapping inclusive ranges of code points
+    ranges: Vec<(u32, u32)>,
+}
+
+/// Repertoire tables for a set of top-level domains
+#[derive(Debug, Clone, Default)]
+pub struct IdnTables {
+    /// Tables keyed by ASCII (punycode) TLD
+    tables: BTreeMap<String, Repertoire>,
+}
+
+/// A code point a registry does not allow
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DisallowedCodePoint {
+    /// Character offset of the code point in the label
+    pub position: usize,
+    /// The character itself
+    pub character: char,
+    /// The code point in `U+XXXX` notation
+    pub code_point: String,
+}
+
+/// The result of checking a registrable label against its TLD's repertoire
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RepertoireReport {
+    /// The label that was checked, in Unicode form (`münchen` for `münchen.de`)
+    pub label: String,
+    /// The top-level domain whose table was used, in ASCII form
+    pub tld: String,
+    /// The code points of `label` outside the table, left to right
+    pub disallowed: Vec<DisallowedCodePoint>,
+}
+
+impl RepertoireReport {
+    /// Returns whether every code point of the label is allowed
+    pub fn is_valid(&self) -> bool {
+        self.disallowed.is_empty()
+    }
+}
+
+impl Repertoire {
+    /// Parses a repertoire table.
+    ///
+    /// Each line holds a code point (`U+00DF`) or an inclusive range
+    /// (`U+00E0..U+00F6`). Blank lines are ignored, as is everything after a
+    /// `#`. Code points are matched after UTS #46 mapping, so a table should
+    /// list lowercase letters only.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - The contents of the table
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Repertoire)` - The parsed table
+    /// * `Err(String)` - An error message with the line number of the first invalid line
+    pub fn parse(data: &str) -> Result<Self, String> {
+        let mut ranges = Vec::new();
+        for (index, line) in data.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let (first, last) = line.split_once("..").unwrap_or((line, line));
+            let range = parse_code_point(first)
+                .zip(parse_code_point(last))
+                .filter(|(first, last)| first <= last)
+                .ok_or_else(|| format!("line {}: invalid code point or range '{}'", index + 1, line))?;
+            ranges.push(range);
+        }
+
+        ranges.sort_unstable();
+        let mut merged: Vec<(u32, u32)> = Vec::with_capacity(ranges.len());
+        for (first, last) in ranges {
+            match merged.last_mut() {
+                Some(previous) if first <= previous.1.saturating_add(1) => previous.1 = previous.1.max(last),
+                _ => merged.push((first, last)),
+            }
+        }
+
+        Ok(Self { ranges: merged })
+    }
+
+    /// Returns whether the repertoire allows a character
+    pub fn contains(&self, c: char) -> bool {
+        let c = c as u32;
+        let index = self.ranges.partition_point(|&(_, last)| last < c);
+        self.ranges.get(index).is_some_and(|&(first, _)| first <= c)
+    }
+
+    /// Lists the characters of a label that the repertoire does not allow
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - A single label in Unicode form
+    pub fn disallowed(&self, label: &str) -> Vec<DisallowedCodePoint> {
+        label
+            .chars()
+            .enumerate()
+            .filter(|&(_, c)| !self.contains(c))
+            .map(|(position, character)| DisallowedCodePoint {
+                position,
+                character,
+                code_point: format!("U+{:04X}", character as u32),
+            })
+            .collect()
+    }
+}
+
+impl IdnTables {
+    /// Returns the tables bundled with the crate, from the `idn_tables` directory.
+    ///
+    /// They are hand-written approximations of the registries' lists, close
+    /// enough to flag labels a registry would not register but not copies of
+    /// the official tables.
+    pub fn builtin() -> &'static Self {
+        BUILTIN.get_or_init(|| {
+            let mut tables = Self::default();
+            for (tld, data) in BUILTIN_TABLES {
+                let repertoire = Repertoire::parse(data)
+                    .unwrap_or_else(|e| panic!("the bundled IDN table for '{}' is invalid: {}", tld, e));
+                tables.tables.insert(tld.to_string(), repertoire);
+            }
+            tables
+        })
+    }
+
+    /// Adds or replaces the table of a top-level domain.
+    ///
+    /// # Arguments
+    ///
+    /// * `tld` - The top-level domain, in Unicode or ASCII form
+    /// * `repertoire` - The code points its registry allows
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - The table was added
+    /// * `Err(String)` - An error message if `tld` is not a valid label
+    pub fn insert(&mut self, tld: &str, repertoire: Repertoire) -> Result<(), String> {
+        let tld = Domain::parse(tld)?;
+        self.tables.insert(tld.into(), repertoire);
+        Ok(())
+    }
+
+    /// Returns the table of a top-level domain, given in ASCII form
+    pub fn get(&self, tld: &str) -> Option<&Repertoire> {
+        self.tables.get(tld)
+    }
+
+    /// Returns the top-level domains that have a table, in ASCII form
+    pub fn tlds(&self) -> impl Iterator<Item = &str> {
+        self.tables.keys().map(String::as_str)
+    }
+
+    /// Checks the registrable label of a host against its TLD's repertoire.
+    ///
+    /// Registries decide which code points may appear in the labels they
+    /// register, so the label checked is the one left of the public suffix
+    /// (`münchen` in `www.münchen.de`); the subdomains are up to the domain
+    /// holder.
+    ///
+    /// # Arguments
+    ///
+    /// * `store` - The Public Suffix List used to find the registrable label
+    /// * `host` - The host to check, in Unicode or punycode form
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Some(RepertoireReport))` - The disallowed code points, if any
+    /// * `Ok(None)` - There is no table for the host's TLD
+    /// * `Err(String)` - An error message if the host is invalid or has no root domain
+    pub fn check(&self, store: &SuffixStore, host: &str) -> Result<Option<RepertoireReport>, String> {
+        let root = Domain::parse(host)?.root_domain_in(store)?;
+        let tld = root.as_str().rsplit('.').next().unwrap_or_default();
+        let Some(repertoire) = self.tables.get(tld) else {
+            return Ok(None);
+        };
+
+        let ascii_label = root.as_str().split('.').next().unwrap_or_default();
+        let (label, _) = idna::domain_to_unicode(ascii_label);
+
+        Ok(Some(RepertoireReport {
+            disallowed: repertoire.disallowed(&label),
+            label,
+            tld: tld.to_string(),
+        }))
+    }
+}
+
+/// Parses a code point in `U+XXXX` notation
+fn parse_code_point(text: &str) -> Option<u32> {
+    let hex = text.trim().strip_prefix("U+")?;
+    let value = u32::from_str_radix(hex, 16).ok()?;
+    char::from_u32(value).map(|_| value)
+}
@@ -0,0 +1,335 @@
+use idna::uts46::{AsciiDenyList, DnsLength, Hyphens, Uts46};
+use idna_adapter::{
+    Adapter, FIRST_BC_MASK, LAST_LTR_MASK, LAST_RTL_MASK, LEFT_OR_DUAL_JOINING_MASK, MIDDLE_LTR_MASK, MIDDLE_RTL_MASK,
+    RIGHT_OR_DUAL_JOINING_MASK, RTL_MASK,
+};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+
+use crate::domain_parser::{self, INVALID_DOMAIN_ERROR};
+
+/// U+200C ZERO WIDTH NON-JOINER
+const ZWNJ: char = '\u{200C}';
+
+/// U+200D ZERO WIDTH JOINER
+const ZWJ: char = '\u{200D}';
+
+/// U+FFFD REPLACEMENT CHARACTER, which UTS #46 mapping puts in place of
+/// disallowed characters
+const REPLACEMENT_CHARACTER: char = '\u{FFFD}';
+
+/// Longest domain name in ASCII form, without a trailing dot
+const MAX_DNS_LENGTH: usize = 253;
+
+/// Longest label in ASCII form
+const MAX_DNS_LABEL_LENGTH: usize = 63;
+
+/// Options for UTS #46 processing of internationalized domain names.
+///
+/// The defaults are those of `idna::domain_to_ascii`, which is how domains
+/// have always been converted and how the URL parser converts hosts:
+/// nontransitional processing, with the bidi and ContextJ rules checked.
+///
+/// See <https://www.unicode.org/reports/tr46/#Processing> for the meaning
+/// of each option.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IdnaOptions {
+    /// Map deviation characters the IDNA2003 way, e.g. `faß.de` to
+    /// `fass.de` instead of `xn--fa-hia.de`
+    pub transitional_processing: bool,
+    /// Reject ASCII characters other than letters, digits and hyphens, e.g.
+    /// the `_` in `_dmarc.example.com`
+    pub use_std3_ascii_rules: bool,
+    /// Reject labels starting or ending with a hyphen
+    pub check_hyphens: bool,
+    /// Apply the bidi rule of RFC 5893 to domains with right-to-left labels
+    pub check_bidi: bool,
+    /// Apply the ContextJ rules of RFC 5892 to zero width joiners and non-joiners
+    pub check_joiners: bool,
+    /// Reject domains longer than 253 characters, labels longer than 63
+    /// characters and empty labels, in ASCII form
+    pub verify_dns_length: bool,
+}
+
+impl Default for IdnaOptions {
+    fn default() -> Self {
+        Self {
+            transitional_processing: false,
+            use_std3_ascii_rules: false,
+            check_hyphens: false,
+            check_bidi: true,
+            check_joiners: true,
+            verify_dns_length: false,
+        }
+    }
+}
+
+impl IdnaOptions {
+    /// Converts a domain to ASCII (punycode) form with these options.
+    ///
+    /// # Arguments
+    ///
+    /// * `domain` - The domain to convert, e.g. `Faß.de` or `例え。jp`
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(String)` - The domain in ASCII form, e.g. `xn--fa-hia.de`
+    /// * `Err(String)` - An error message naming the checks that failed, or the
+    ///   first fake or broken punycode label
+    pub fn to_ascii(&self, domain: &str) -> Result<String, String> {
+        // idna decodes `xn--` labels leniently, e.g. `xn--example-` to `example`
+        domain_parser::check_punycode_labels(domain)?;
+
+        let mapped = if self.transitional_processing {
+            map_transitional(domain)
+        } else {
+            Cow::Borrowed(domain)
+        };
+        let deny_list = if self.use_std3_ascii_rules {
+            AsciiDenyList::STD3
+        } else {
+            AsciiDenyList::EMPTY
+        };
+        let hyphens = if self.check_hyphens {
+            Hyphens::Check
+        } else {
+            Hyphens::Allow
+        };
+
+        // idna always applies the bidi and ContextJ rules and does not say
+        // which check failed, so a rejected domain is checked label by label
+        let (ascii, mut failures) =
+            match Uts46::new().to_ascii(mapped.as_bytes(), deny_list, hyphens, DnsLength::Ignore) {
+                Ok(ascii) => (ascii.into_owned(), Vec::new()),
+                Err(_) => self.check_labels(&mapped),
+            };
+        if self.verify_dns_length {
+            let trimmed = ascii.strip_suffix('.').unwrap_or(&ascii);
+            if trimmed.is_empty() || trimmed.split('.').any(str::is_empty) {
+                failures.push("too_short_for_dns");
+            }
+            if trimmed.len() > MAX_DNS_LENGTH || trimmed.split('.').any(|label| label.len() > MAX_DNS_LABEL_LENGTH) {
+                failures.push("too_long_for_dns");
+            }
+        }
+
+        if failures.is_empty() {
+            Ok(ascii)
+        } else {
+            Err(format!(
+                "{}: '{}' ({})",
+                INVALID_DOMAIN_ERROR,
+                domain,
+                failures.join(", ")
+            ))
+        }
+    }
+
+    /// Runs the UTS #46 validity checks on each label of a domain that idna
+    /// rejected, returning the domain in ASCII form and the names of the
+    /// checks that failed, leaving out the bidi and ContextJ rules when they
+    /// are switched off.
+    fn check_labels(&self, domain: &str) -> (String, Vec<&'static str>) {
+        let unicode = Adapter::new();
+        let mut failures = Vec::new();
+
+        let mapped: String = unicode.map_normalize(domain.chars()).collect();
+        let mut labels = Vec::new();
+        for label in mapped.split('.') {
+            let label = match label.strip_prefix("xn--") {
+                Some(encoded) => match idna::punycode::decode_to_string(encoded) {
+                    Some(decoded) => {
+                        let validated: String = unicode.normalize_validate(decoded.chars()).collect();
+                        if validated != decoded && !validated.contains(REPLACEMENT_CHARACTER) {
+                            fail(&mut failures, "nfc");
+                        }
+                        validated
+                    }
+                    None => {
+                        fail(&mut failures, "punycode");
+                        label.to_string()
+                    }
+                },
+                None => label.to_string(),
+            };
+
+            if label.contains(REPLACEMENT_CHARACTER) {
+                fail(&mut failures, "disallowed_character");
+            }
+            if self.use_std3_ascii_rules
+                && label
+                    .chars()
+                    .any(|c| c.is_ascii() && !c.is_ascii_alphanumeric() && c != '-')
+            {
+                fail(&mut failures, "disallowed_by_std3_ascii_rules");
+            }
+            if self.check_hyphens && (label.starts_with('-') || label.ends_with('-') || label.get(2..4) == Some("--")) {
+                fail(&mut failures, "check_hyphens");
+            }
+            if label.chars().next().is_some_and(|c| unicode.is_mark(c)) {
+                fail(&mut failures, "start_combining_mark");
+            }
+            if self.check_joiners && !passes_context_j(&unicode, &label) {
+                fail(&mut failures, "check_joiners");
+            }
+            labels.push(label);
+        }
+
+        let is_bidi = labels
+            .iter()
+            .flat_map(|label| label.chars())
+            .any(|c| RTL_MASK.intersects(unicode.bidi_class(c).to_mask()));
+        if self.check_bidi && is_bidi && !labels.iter().all(|label| passes_bidi_rule(&unicode, label)) {
+            fail(&mut failures, "check_bidi");
+        }
+
+        let mut ascii = String::with_capacity(domain.len());
+        for (i, label) in labels.iter().enumerate() {
+            if i > 0 {
+                ascii.push('.');
+            }
+            if label.is_ascii() {
+                ascii.push_str(label);
+            } else if let Some(encoded) = idna::punycode::encode_str(label) {
+                ascii.push_str("xn--");
+                ascii.push_str(&encoded);
+            } else {
+                fail(&mut failures, "punycode");
+            }
+        }
+
+        // A check idna applies but that is not run here, so the domain is
+        // never let through
+        if failures.is_empty() && self.check_bidi && self.check_joiners {
+            fail(&mut failures, "invalid_label");
+        }
+        (ascii, failures)
+    }
+}
+
+/// Converts a domain to Unicode form, keeping the labels that fail the
+/// UTS #46 checks as decoded instead of replacing them with U+FFFD as
+/// `idna::domain_to_unicode` does
+///
+/// # Arguments
+///
+/// * `domain` - The domain to convert, e.g. `xn--a-0hc.com`
+///
+/// # Returns
+///
+/// The domain in Unicode form, e.g. `aא.com`
+pub(crate) fn to_unicode(domain: &str) -> String {
+    let (unicode, result) = idna::domain_to_unicode(domain);
+    if result.is_ok() {
+        return unicode;
+    }
+
+    let mapped: String = Adapter::new().map_normalize(domain.chars()).collect();
+    mapped
+        .split('.')
+        .map(|label| {
+            label
+                .strip_prefix("xn--")
+                .and_then(idna::punycode::decode_to_string)
+                .unwrap_or_else(|| label.to_string())
+        })
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+/// Records a failed check once
+fn fail(failures: &mut Vec<&'static str>, name: &'static str) {
+    if !failures.contains(&name) {
+        failures.push(name);
+    }
+}
+
+/// Maps the deviation characters the IDNA2003 way: `ß` to `ss`, final `ς`
+/// to `σ`, and zero width joiners and non-joiners to nothing
+fn map_transitional(domain: &str) -> Cow<'_, str> {
+    if !domain.contains(['ß', 'ẞ', 'ς', ZWNJ, ZWJ]) {
+        return Cow::Borrowed(domain);
+    }
+    let mut mapped = String::with_capacity(domain.len());
+    for c in domain.chars() {
+        match c {
+            'ß' | 'ẞ' => mapped.push_str("ss"),
+            'ς' => mapped.push('σ'),
+            ZWNJ | ZWJ => {}
+            c => mapped.push(c),
+        }
+    }
+    Cow::Owned(mapped)
+}
+
+/// Checks a label against the bidi rule of RFC 5893, section 2, which
+/// applies to every label of a domain with a right-to-left label
+fn passes_bidi_rule(unicode: &Adapter, label: &str) -> bool {
+    let mut classes = label.chars().map(|c| unicode.bidi_class(c));
+    let Some(first) = classes.next() else {
+        return true;
+    };
+    if !FIRST_BC_MASK.intersects(first.to_mask()) {
+        return false;
+    }
+
+    // Trailing nonspacing marks are skipped when checking the last character
+    let rest: Vec<_> = classes.collect();
+    let Some(last) = rest.iter().rposition(|class| !class.is_nonspacing_mark()) else {
+        return true;
+    };
+    let (middle_mask, last_mask) = if first.is_ltr() {
+        (MIDDLE_LTR_MASK, LAST_LTR_MASK)
+    } else {
+        (MIDDLE_RTL_MASK, LAST_RTL_MASK)
+    };
+    if !last_mask.intersects(rest[last].to_mask())
+        || !rest[..last].iter().all(|class| middle_mask.intersects(class.to_mask()))
+    {
+        return false;
+    }
+
+    // A right-to-left label cannot mix European and Arabic digits
+    first.is_ltr()
+        || !(rest[..=last].iter().any(|class| class.is_european_number())
+            && rest[..=last].iter().any(|class| class.is_arabic_number()))
+}
+
+/// Checks the zero width joiners of a label against the ContextJ rules of
+/// RFC 5892, appendix A.1 and A.2
+fn passes_context_j(unicode: &Adapter, label: &str) -> bool {
+    let chars: Vec<char> = label.chars().collect();
+    for (i, &c) in chars.iter().enumerate() {
+        if c != ZWNJ && c != ZWJ {
+            continue;
+        }
+
+        // Both are allowed after a virama
+        if i > 0 && unicode.is_virama(chars[i - 1]) {
+            continue;
+        }
+        if c == ZWJ {
+            return false;
+        }
+
+        // A non-joiner is also allowed between a character joining to the
+        // right and one joining to the left, skipping transparent ones
+        let before = chars[..i]
+            .iter()
+            .rev()
+            .map(|&c| unicode.joining_type(c))
+            .find(|joining_type| !joining_type.is_transparent());
+        let after = chars[i + 1..]
+            .iter()
+            .map(|&c| unicode.joining_type(c))
+            .find(|joining_type| !joining_type.is_transparent());
+        if !before.is_some_and(|joining_type| LEFT_OR_DUAL_JOINING_MASK.intersects(joining_type.to_mask()))
+            || !after.is_some_and(|joining_type| RIGHT_OR_DUAL_JOINING_MASK.intersects(joining_type.to_mask()))
+        {
+            return false;
+        }
+    }
+
+    true
+}
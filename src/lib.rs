@@ -1,2 +1,27 @@
+pub mod defang;
+pub mod domain;
 pub mod domain_parser;
-pub mod api; 
\ No newline at end of file
+pub mod homograph;
+pub mod idn_display;
+pub mod idn_tables;
+pub mod idna_options;
+pub mod lookalike;
+pub mod psl;
+pub mod psl_diff;
+pub mod redirect;
+pub mod suffix_store;
+pub mod suffix_trie;
+pub mod typosquat;
+pub mod url_diagnostics;
+#[cfg(feature = "server")]
+pub mod api;
+#[cfg(feature = "server")]
+pub mod batch;
+#[cfg(feature = "parallel")]
+pub mod bulk;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "python")]
+pub mod python;
@@ -0,0 +1,234 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+use unicode_security::skeleton;
+
+use crate::domain::{Domain, RootDomain};
+use crate::domain_parser;
+use crate::suffix_store::SuffixStore;
+
+/// A set of protected root domains, indexed by UTS #39 confusable skeleton
+#[derive(Debug, Clone, Default)]
+pub struct ProtectedDomains {
+    /// Protected root domains in Unicode form, in the order they were added
+    domains: Vec<String>,
+    /// The ASCII forms of `domains`, to recognize the protected domains themselves
+    ascii: HashSet<String>,
+    /// Indices into `domains`, keyed by skeleton
+    by_skeleton: HashMap<String, Vec<usize>>,
+}
+
+/// A host whose root domain looks like a protected domain without being it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Lookalike {
+    /// The root domain of the checked host, in Unicode form
+    pub root_domain: String,
+    /// The protected domain it imitates, in Unicode form
+    pub protected_domain: String,
+    /// Where the two differ, left to right
+    pub differences: Vec<Difference>,
+}
+
+/// A run of characters in a lookalike that stands in for other characters
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Difference {
+    /// Character offset of the run in the lookalike root domain
+    pub position: usize,
+    /// The characters found in the lookalike, e.g. a Cyrillic `а` or `rn`
+    pub found: String,
+    /// The characters of the protected domain they imitate, e.g. `a` or `m`
+    pub expected: String,
+}
+
+impl ProtectedDomains {
+    /// Builds the set from a list of domains.
+    ///
+    /// Every domain is reduced to its root domain, so `www.example.com`
+    /// protects `example.com`.
+    ///
+    /// # Arguments
+    ///
+    /// * `store` - The Public Suffix List used to find root domains
+    /// * `domains` - The domains to protect
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(ProtectedDomains)` - The indexed set
+    /// * `Err(String)` - An error message naming the first invalid domain
+    pub fn new<I>(store: &SuffixStore, domains: I) -> Result<Self, String>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        let mut protected = Self::default();
+        for domain in domains {
+            protected.insert(store, domain.as_ref())?;
+        }
+        Ok(protected)
+    }
+
+    /// Loads the set from a file with one domain per line.
+    ///
+    /// Blank lines and lines starting with `#` are ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `store` - The Public Suffix List used to find root domains
+    /// * `path` - The path of the file
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(ProtectedDomains)` - The indexed set
+    /// * `Err(String)` - An error message if the file cannot be read or has an
+    ///   invalid domain, with its line number
+    pub fn from_file<P: AsRef<Path>>(store: &SuffixStore, path: P) -> Result<Self, String> {
+        let path = path.as_ref();
+        let data = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read protected domains from {}: {}", path.display(), e))?;
+
+        let mut protected = Self::default();
+        for (index, line) in data.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            protected
+                .insert(store, line)
+                .map_err(|e| format!("{}:{}: {}", path.display(), index + 1, e))?;
+        }
+        Ok(protected)
+    }
+
+    /// Returns the number of protected root domains
+    pub fn len(&self) -> usize {
+        self.domains.len()
+    }
+
+    /// Returns whether no domains are protected
+    pub fn is_empty(&self) -> bool {
+        self.domains.is_empty()
+    }
+
+    /// Checks whether a host's root domain is confusable with a protected domain.
+    ///
+    /// The root domain is converted to Unicode and its skeleton looked up in
+    /// the index, so the cost does not depend on the number of protected
+    /// domains. A protected domain and its subdomains are never reported.
+    ///
+    /// # Arguments
+    ///
+    /// * `store` - The Public Suffix List used to find the root domain
+    /// * `host` - The host to check, in Unicode or punycode form
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Some(Lookalike))` - The protected domain the host imitates
+    /// * `Ok(None)` - The host does not look like any protected domain
+    /// * `Err(String)` - An error message if the host has no root domain
+    pub fn check(&self, store: &SuffixStore, host: &str) -> Result<Option<Lookalike>, String> {
+        let root = Domain::parse(host)?.root_domain_in(store)?;
+        if self.ascii.contains(root.as_str()) {
+            return Ok(None);
+        }
+
+        let root_domain = root.as_domain().to_unicode();
+        let key: String = skeleton(&root_domain).collect();
+        let Some(&index) = self.by_skeleton.get(&key).and_then(|indices| indices.first()) else {
+            return Ok(None);
+        };
+
+        let protected_domain = self.domains[index].clone();
+        Ok(Some(Lookalike {
+            differences: differences(&root_domain, &protected_domain),
+            root_domain,
+            protected_domain,
+        }))
+    }
+
+    /// Checks whether the host of a URL is confusable with a protected domain.
+    ///
+    /// See [`Self::check`].
+    ///
+    /// # Arguments
+    ///
+    /// * `store` - The Public Suffix List used to find the root domain
+    /// * `url_str` - The URL to check
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Some(Lookalike))` - The protected domain the host imitates
+    /// * `Ok(None)` - The host does not look like any protected domain
+    /// * `Err(String)` - An error message if the URL is invalid or has no root domain
+    pub fn check_url(&self, store: &SuffixStore, url_str: &str) -> Result<Option<Lookalike>, String> {
+        let host = domain_parser::parse_url(url_str)?;
+        self.check(store, &host)
+    }
+
+    /// Adds the root domain of `domain` to the set
+    fn insert(&mut self, store: &SuffixStore, domain: &str) -> Result<(), String> {
+        let root: RootDomain = Domain::parse(domain)?.root_domain_in(store)?;
+        if !self.ascii.insert(root.as_str().to_string()) {
+            return Ok(());
+        }
+
+        let unicode = root.as_domain().to_unicode();
+        self.by_skeleton
+            .entry(skeleton(&unicode).collect())
+            .or_default()
+            .push(self.domains.len());
+        self.domains.push(unicode);
+        Ok(())
+    }
+}
+
+/// Lines up two strings with the same skeleton and lists the runs that differ
+fn differences(found: &str, expected: &str) -> Vec<Difference> {
+    let found: Vec<char> = found.chars().collect();
+    let expected: Vec<char> = expected.chars().collect();
+    let char_skeleton = |c: char| -> String { skeleton(c.encode_utf8(&mut [0; 4])).collect() };
+
+    let mut differences = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < found.len() && j < expected.len() {
+        // Grow a run on whichever side has the shorter skeleton until both
+        // runs have the same skeleton, e.g. `rn` against `m`
+        let (start_i, start_j) = (i, j);
+        let mut found_skeleton = char_skeleton(found[i]);
+        let mut expected_skeleton = char_skeleton(expected[j]);
+        i += 1;
+        j += 1;
+        while found_skeleton != expected_skeleton {
+            if found_skeleton.len() < expected_skeleton.len() && i < found.len() {
+                found_skeleton.push_str(&char_skeleton(found[i]));
+                i += 1;
+            } else if j < expected.len() {
+                expected_skeleton.push_str(&char_skeleton(expected[j]));
+                j += 1;
+            } else if i < found.len() {
+                found_skeleton.push_str(&char_skeleton(found[i]));
+                i += 1;
+            } else {
+                break;
+            }
+        }
+
+        if found[start_i..i] != expected[start_j..j] {
+            differences.push(Difference {
+                position: start_i,
+                found: found[start_i..i].iter().collect(),
+                expected: expected[start_j..j].iter().collect(),
+            });
+        }
+    }
+
+    if i < found.len() || j < expected.len() {
+        differences.push(Difference {
+            position: i,
+            found: found[i..].iter().collect(),
+            expected: expected[j..].iter().collect(),
+        });
+    }
+
+    differences
+}
@@ -1,11 +1,354 @@
-use domain_validator::api;
+use clap::{Parser, Subcommand, ValueEnum};
+use domain_validator::psl::{self, ParseMode};
+use domain_validator::suffix_store::{SuffixStore, UrlBreakdown};
+use domain_validator::suffix_trie::SuffixTrie;
+use domain_validator::batch::{self, BatchProgress, BatchSummary};
+use domain_validator::{api, psl_diff};
+use serde_json::Value;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::{Duration, Instant};
+
+/// Extracts root domains from URLs using the Public Suffix List
+#[derive(Debug, Parser)]
+#[command(name = "domain-validator", version)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Start the HTTP API on port 3000
+    Serve,
+    /// Print the root domain of each URL, read from the arguments or from stdin
+    Parse {
+        /// URLs to parse; reads one URL per line from stdin if none are given or for `-`
+        urls: Vec<String>,
+        /// Output format
+        #[arg(short, long, value_enum, default_value_t = OutputFormat::Plain)]
+        format: OutputFormat,
+        /// Print the host, suffix, subdomain and PSL section as well as the root domain
+        #[arg(long)]
+        full: bool,
+        /// Public Suffix List file to use instead of the default one
+        #[arg(long, value_name = "FILE")]
+        psl: Option<PathBuf>,
+    },
+    /// Annotate a CSV or JSON Lines file with root domain, suffix and error columns
+    Batch {
+        /// Input file, or `-` for stdin
+        input: PathBuf,
+        /// Output file; defaults to stdout
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+        /// Input format; inferred from the file extension if omitted
+        #[arg(short, long, value_enum)]
+        format: Option<BatchFormat>,
+        /// CSV column holding the URL [default: url]
+        #[arg(long, value_name = "NAME")]
+        column: Option<String>,
+        /// JSON pointer to the URL in each JSON Lines record [default: /url]
+        #[arg(long)]
+        pointer: Option<String>,
+        /// Public Suffix List file to use instead of the default one
+        #[arg(long, value_name = "FILE")]
+        psl: Option<PathBuf>,
+        /// Do not report progress on stderr
+        #[arg(short, long)]
+        quiet: bool,
+    },
+    /// Compare two Public Suffix List files
+    PslDiff {
+        old: PathBuf,
+        new: PathBuf,
+        /// File with one host per line whose root domain changes are reported
+        hosts: Option<PathBuf>,
+    },
+    /// Compile a Public Suffix List file to a binary suffix trie
+    PslCompile { input: PathBuf, output: PathBuf },
+}
+
+/// Output formats of the `parse` command
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum OutputFormat {
+    /// The root domain on its own line, errors on stderr
+    Plain,
+    /// Tab-separated values with a header row
+    Tsv,
+    /// One JSON object per line
+    Json,
+}
+
+/// Input formats of the `batch` command
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum BatchFormat {
+    /// Comma-separated values with a header row
+    Csv,
+    /// One JSON object per line
+    Jsonl,
+}
+
+/// How often `batch` reports progress
+const PROGRESS_INTERVAL: Duration = Duration::from_secs(5);
 
 #[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    println!("Starting Domain Validator Web Service...");
-    println!("Server will be available at http://localhost:3000");
-    println!("Use the endpoint: http://localhost:3000/parse?url=<your-url>");
-    println!("Press Ctrl+C to stop the server");
-    
-    api::start_service().await
+async fn main() -> Result<ExitCode, Box<dyn std::error::Error>> {
+    match Cli::parse().command {
+        Command::Serve => {
+            println!("Starting Domain Validator Web Service...");
+            println!("Server will be available at http://localhost:3000");
+            println!("Use the endpoint: http://localhost:3000/parse?url=<your-url>");
+            println!("Press Ctrl+C to stop the server");
+
+            api::start_service().await?;
+            Ok(ExitCode::SUCCESS)
+        }
+        Command::Parse { urls, format, full, psl } => run_parse(urls, format, full, psl.as_deref()),
+        Command::Batch {
+            input,
+            output,
+            format,
+            column,
+            pointer,
+            psl,
+            quiet,
+        } => {
+            let format = match format.or_else(|| infer_batch_format(&input)) {
+                Some(format) => format,
+                None => return Err("Cannot infer the input format, pass --format csv or --format jsonl".into()),
+            };
+            let field = match (format, column, pointer) {
+                (BatchFormat::Csv, _, Some(_)) => return Err("--pointer only applies to JSON Lines input".into()),
+                (BatchFormat::Jsonl, Some(_), _) => return Err("--column only applies to CSV input".into()),
+                (BatchFormat::Csv, column, None) => column.unwrap_or_else(|| "url".to_string()),
+                (BatchFormat::Jsonl, None, pointer) => pointer.unwrap_or_else(|| "/url".to_string()),
+            };
+            let summary = run_batch(&input, output.as_deref(), format, &field, psl.as_deref(), quiet)?;
+            eprint!("{}", summary);
+            Ok(ExitCode::SUCCESS)
+        }
+        Command::PslDiff { old, new, hosts } => run_psl_diff(&old, &new, hosts.as_deref()).map(|()| ExitCode::SUCCESS),
+        Command::PslCompile { input, output } => run_psl_compile(&input, &output).map(|()| ExitCode::SUCCESS),
+    }
+}
+
+/// Parses URLs and prints their root domains; fails if any URL is invalid
+fn run_parse(
+    urls: Vec<String>,
+    format: OutputFormat,
+    full: bool,
+    psl_path: Option<&Path>,
+) -> Result<ExitCode, Box<dyn std::error::Error>> {
+    let store = match psl_path {
+        Some(path) => SuffixStore::from_file(path)?,
+        None => SuffixStore::load_default()?,
+    };
+
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    if format == OutputFormat::Tsv {
+        let header = if full { FULL_COLUMNS } else { COLUMNS };
+        writeln!(out, "{}", header.join("\t"))?;
+    }
+
+    let mut failures = 0;
+    let mut parse = |url: &str, out: &mut dyn Write| -> io::Result<()> {
+        let result = store.breakdown_url(url);
+        if result.is_err() {
+            failures += 1;
+        }
+        write_parse_result(out, url, &result, format, full)
+    };
+
+    if urls.is_empty() || urls == ["-"] {
+        for line in io::stdin().lock().lines() {
+            let line = line?;
+            let url = line.trim();
+            if !url.is_empty() {
+                parse(url, &mut out)?;
+            }
+        }
+    } else {
+        for url in &urls {
+            parse(url, &mut out)?;
+        }
+    }
+
+    Ok(if failures > 0 { ExitCode::FAILURE } else { ExitCode::SUCCESS })
+}
+
+/// Columns printed by `parse`, in output order
+const COLUMNS: &[&str] = &["input", "root_domain", "error"];
+
+/// Columns printed by `parse --full`, in output order
+const FULL_COLUMNS: &[&str] = &["input", "host", "root_domain", "suffix", "subdomain", "section", "error"];
+
+/// Returns the value of an output column for one URL
+fn column(name: &str, url: &str, result: &Result<UrlBreakdown, String>) -> Option<String> {
+    let breakdown = match (name, result) {
+        ("input", _) => return Some(url.to_string()),
+        ("error", result) => return result.as_ref().err().cloned(),
+        (_, Ok(breakdown)) => breakdown,
+        (_, Err(_)) => return None,
+    };
+    match name {
+        "host" => Some(breakdown.host.clone()),
+        "root_domain" => Some(breakdown.root_domain.clone()),
+        "suffix" => Some(breakdown.suffix.clone()),
+        "subdomain" => breakdown.subdomain.clone(),
+        "section" => breakdown.section.map(|section| section.to_string().to_lowercase()),
+        _ => None,
+    }
+}
+
+/// Writes the result for one URL in the requested format
+fn write_parse_result(
+    out: &mut dyn Write,
+    url: &str,
+    result: &Result<UrlBreakdown, String>,
+    format: OutputFormat,
+    full: bool,
+) -> io::Result<()> {
+    let columns = if full { FULL_COLUMNS } else { COLUMNS };
+
+    match (format, result) {
+        (OutputFormat::Plain, Err(e)) => {
+            eprintln!("{}: {}", url, e);
+            Ok(())
+        }
+        (OutputFormat::Plain, Ok(breakdown)) if !full => writeln!(out, "{}", breakdown.root_domain),
+        (OutputFormat::Plain, Ok(_)) => {
+            writeln!(out, "{}", url)?;
+            for name in &columns[1..columns.len() - 1] {
+                writeln!(out, "  {}: {}", name, column(name, url, result).unwrap_or_default())?;
+            }
+            Ok(())
+        }
+        (OutputFormat::Tsv, _) => {
+            let fields: Vec<String> = columns
+                .iter()
+                .map(|name| column(name, url, result).unwrap_or_default().replace(['\t', '\n', '\r'], " "))
+                .collect();
+            writeln!(out, "{}", fields.join("\t"))
+        }
+        (OutputFormat::Json, _) => {
+            // Built by hand so the keys keep the column order
+            let fields: Vec<String> = columns
+                .iter()
+                .map(|name| format!("{}:{}", Value::from(*name), column(name, url, result).map_or(Value::Null, Value::String)))
+                .collect();
+            writeln!(out, "{{{}}}", fields.join(","))
+        }
+    }
+}
+
+/// Guesses the format of a `batch` input from its file extension
+fn infer_batch_format(path: &Path) -> Option<BatchFormat> {
+    match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+        "csv" => Some(BatchFormat::Csv),
+        "jsonl" | "ndjson" => Some(BatchFormat::Jsonl),
+        _ => None,
+    }
+}
+
+/// Streams a CSV or JSON Lines file through the batch annotator
+fn run_batch(
+    input_path: &Path,
+    output_path: Option<&Path>,
+    format: BatchFormat,
+    field: &str,
+    psl_path: Option<&Path>,
+    quiet: bool,
+) -> Result<BatchSummary, Box<dyn std::error::Error>> {
+    let store = match psl_path {
+        Some(path) => SuffixStore::from_file(path)?,
+        None => SuffixStore::load_default()?,
+    };
+
+    let (input, total_bytes): (Box<dyn BufRead>, Option<u64>) = if input_path == Path::new("-") {
+        (Box::new(io::stdin().lock()), None)
+    } else {
+        let file = File::open(input_path).map_err(|e| format!("{}: {}", input_path.display(), e))?;
+        let size = file.metadata().ok().map(|metadata| metadata.len());
+        (Box::new(BufReader::new(file)), size)
+    };
+    let output: Box<dyn Write> = match output_path {
+        Some(path) => Box::new(File::create(path).map_err(|e| format!("{}: {}", path.display(), e))?),
+        None => Box::new(io::stdout().lock()),
+    };
+
+    let mut last_report = Instant::now();
+    let progress = |progress: BatchProgress| {
+        if quiet || last_report.elapsed() < PROGRESS_INTERVAL {
+            return;
+        }
+        last_report = Instant::now();
+        match total_bytes {
+            Some(total) if total > 0 => eprintln!(
+                "{} records processed ({:.1}% of input)",
+                progress.records,
+                progress.bytes_read as f64 * 100.0 / total as f64
+            ),
+            _ => eprintln!("{} records processed", progress.records),
+        }
+    };
+
+    let summary = match format {
+        BatchFormat::Csv => batch::annotate_csv(&store, input, output, field, progress)?,
+        BatchFormat::Jsonl => batch::annotate_jsonl(&store, input, output, field, progress)?,
+    };
+    Ok(summary)
+}
+
+/// Compares two PSL files: `psl-diff <old.dat> <new.dat> [hosts.txt]`
+fn run_psl_diff(old_path: &Path, new_path: &Path, hosts_path: Option<&Path>) -> Result<(), Box<dyn std::error::Error>> {
+    let old_data = fs::read_to_string(old_path)?;
+    let new_data = fs::read_to_string(new_path)?;
+    let hosts_data = match hosts_path {
+        Some(path) => fs::read_to_string(path)?,
+        None => String::new(),
+    };
+
+    // One host per line, ignoring blank lines and '#' comments
+    let hosts = hosts_data
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'));
+
+    let diff = psl_diff::diff_lists(&old_data, &new_data, hosts)?;
+    print!("{}", diff);
+
+    Ok(())
+}
+
+/// Compiles a PSL file to a binary suffix trie: `psl-compile <input.dat> <output.bin>`
+fn run_psl_compile(input_path: &Path, output: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let data = fs::read_to_string(input_path)?;
+    let input = input_path.display();
+    let parsed = match psl::parse_list(&data, ParseMode::Strict) {
+        Ok(parsed) => parsed,
+        Err(errors) => {
+            for error in &errors {
+                eprintln!("{}: {}", input, error);
+            }
+            return Err(format!("{} malformed entries in {}", errors.len(), input).into());
+        }
+    };
+
+    let trie = SuffixTrie::compile(&parsed)?;
+    fs::write(output, trie.as_bytes())?;
+
+    let (icann, private) = trie.rule_counts();
+    println!(
+        "Compiled {} ICANN and {} private rules into {} ({} bytes)",
+        icann,
+        private,
+        output.display(),
+        trie.as_bytes().len()
+    );
+
+    Ok(())
 }
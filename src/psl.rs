@@ -0,0 +1,350 @@
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+/// Section of the Public Suffix List a rule belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Section {
+    Icann,
+    Private,
+}
+
+impl fmt::Display for Section {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Section::Icann => write!(f, "ICANN"),
+            Section::Private => write!(f, "PRIVATE"),
+        }
+    }
+}
+
+/// The kind of a Public Suffix List rule
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RuleKind {
+    /// A plain suffix such as `co.uk`
+    Normal,
+    /// A rule whose leftmost label is `*`, such as `*.ck`
+    Wildcard,
+    /// A rule starting with `!` that overrides a wildcard, such as `!www.ck`
+    Exception,
+}
+
+/// A single rule read from a Public Suffix List file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rule {
+    /// The rule exactly as written in the list (e.g. `co.uk`, `*.ck`, `!www.ck`)
+    pub text: String,
+    /// Whether the rule is a normal, wildcard or exception rule
+    pub kind: RuleKind,
+    /// The section the rule was declared in
+    pub section: Section,
+    /// 1-based line number of the rule in the source file
+    pub line: usize,
+}
+
+/// A `===BEGIN ... DOMAINS===` / `===END ... DOMAINS===` pair found in the list
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SectionMarker {
+    pub section: Section,
+    /// 1-based line number of the BEGIN marker
+    pub begin_line: usize,
+    /// 1-based line number of the END marker, if the section was closed
+    pub end_line: Option<usize>,
+}
+
+/// How the parser treats malformed entries
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ParseMode {
+    /// Any malformed entry fails the whole parse
+    #[default]
+    Strict,
+    /// Malformed entries are skipped and reported as diagnostics
+    Lenient,
+}
+
+/// The reason a Public Suffix List entry was rejected
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PslErrorKind {
+    /// The rule contains a character that is not allowed in a domain label
+    InvalidCharacter(char),
+    /// The rule contains an empty label (leading, trailing or doubled dot)
+    EmptyLabel,
+    /// The rule is not a valid internationalized domain name
+    InvalidIdn,
+    /// A `*` appears somewhere other than as the whole leftmost label
+    MisplacedWildcard,
+    /// The same rule was already declared on an earlier line
+    DuplicateRule { first_line: usize },
+    /// An exception rule has no `*.` rule covering its parent
+    ExceptionWithoutWildcard,
+}
+
+/// A malformed entry in a Public Suffix List file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PslError {
+    /// 1-based line number of the entry
+    pub line: usize,
+    /// The rule as written in the list
+    pub rule: String,
+    pub kind: PslErrorKind,
+}
+
+impl fmt::Display for PslError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: ", self.line)?;
+        match &self.kind {
+            PslErrorKind::InvalidCharacter(c) => {
+                write!(f, "invalid character {:?} in rule '{}'", c, self.rule)
+            }
+            PslErrorKind::EmptyLabel => write!(f, "empty label in rule '{}'", self.rule),
+            PslErrorKind::InvalidIdn => {
+                write!(f, "rule '{}' is not a valid internationalized domain name", self.rule)
+            }
+            PslErrorKind::MisplacedWildcard => {
+                write!(f, "misplaced wildcard in rule '{}'", self.rule)
+            }
+            PslErrorKind::DuplicateRule { first_line } => {
+                write!(f, "duplicate rule '{}' (first declared on line {})", self.rule, first_line)
+            }
+            PslErrorKind::ExceptionWithoutWildcard => {
+                write!(f, "exception rule '{}' has no matching wildcard rule", self.rule)
+            }
+        }
+    }
+}
+
+impl std::error::Error for PslError {}
+
+/// The result of parsing a Public Suffix List file
+#[derive(Debug, Clone, Default)]
+pub struct ParsedList {
+    /// The `// VERSION:` header value, if present
+    pub version: Option<String>,
+    /// The `// COMMIT:` header value, if present
+    pub commit: Option<String>,
+    /// The valid rules in the order they appear in the file
+    pub rules: Vec<Rule>,
+    /// The section markers found in the file
+    pub sections: Vec<SectionMarker>,
+    /// Entries skipped in lenient mode (always empty in strict mode)
+    pub diagnostics: Vec<PslError>,
+}
+
+/// Parses the contents of a Public Suffix List file.
+///
+/// Only lines between the `===BEGIN ... DOMAINS===` and `===END ... DOMAINS===`
+/// markers are considered. Comments and blank lines are skipped, and only the
+/// first whitespace-separated token of a line is used as the rule.
+///
+/// Each rule is checked for invalid characters, empty labels, misplaced
+/// wildcards, duplicates (compared case-insensitively in punycode form) and
+/// exceptions that have no wildcard rule to override.
+///
+/// # Arguments
+///
+/// * `data` - The contents of a Public Suffix List file
+/// * `mode` - Whether malformed entries fail the parse or are skipped
+///
+/// # Returns
+///
+/// * `Ok(ParsedList)` - The rules, section markers and any skipped entries
+/// * `Err(Vec<PslError>)` - Every malformed entry, in line order (strict mode only)
+pub fn parse_list(data: &str, mode: ParseMode) -> Result<ParsedList, Vec<PslError>> {
+    let mut parsed = ParsedList::default();
+    let mut errors = Vec::new();
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    let mut section = None;
+
+    for (index, line) in data.lines().enumerate() {
+        let line_number = index + 1;
+        let line = line.trim();
+
+        if let Some(comment) = line.strip_prefix("//") {
+            if let Some(version) = header_field(comment, "VERSION") {
+                parsed.version.get_or_insert(version);
+                continue;
+            }
+            if let Some(commit) = header_field(comment, "COMMIT") {
+                parsed.commit.get_or_insert(commit);
+                continue;
+            }
+            match section_marker(comment) {
+                Some(Some(begin)) => {
+                    section = Some(begin);
+                    parsed.sections.push(SectionMarker {
+                        section: begin,
+                        begin_line: line_number,
+                        end_line: None,
+                    });
+                }
+                Some(None) => {
+                    section = None;
+                    if let Some(marker) = parsed.sections.last_mut() {
+                        marker.end_line.get_or_insert(line_number);
+                    }
+                }
+                None => {}
+            }
+            continue;
+        }
+
+        let (Some(section), Some(text)) = (section, line.split_whitespace().next()) else {
+            continue;
+        };
+
+        let error = |kind| PslError {
+            line: line_number,
+            rule: text.to_string(),
+            kind,
+        };
+
+        let (kind, key) = match validate_rule(text) {
+            Ok(rule) => rule,
+            Err(kind) => {
+                errors.push(error(kind));
+                continue;
+            }
+        };
+
+        if let Some(&first_line) = seen.get(&key) {
+            errors.push(error(PslErrorKind::DuplicateRule { first_line }));
+            continue;
+        }
+        seen.insert(key, line_number);
+
+        parsed.rules.push(Rule {
+            text: text.to_string(),
+            kind,
+            section,
+            line: line_number,
+        });
+    }
+
+    // Exceptions may legally appear before the wildcard they override,
+    // so they can only be checked once every rule is known
+    let wildcards: HashSet<String> = parsed
+        .rules
+        .iter()
+        .filter(|rule| rule.kind == RuleKind::Wildcard)
+        .map(|rule| normalize_rule(&rule.text[2..]))
+        .collect();
+
+    parsed.rules.retain(|rule| {
+        if rule.kind != RuleKind::Exception {
+            return true;
+        }
+        let parent = match rule.text[1..].split_once('.') {
+            Some((_, parent)) => normalize_rule(parent),
+            None => String::new(),
+        };
+        if wildcards.contains(&parent) {
+            return true;
+        }
+        errors.push(PslError {
+            line: rule.line,
+            rule: rule.text.clone(),
+            kind: PslErrorKind::ExceptionWithoutWildcard,
+        });
+        false
+    });
+
+    errors.sort_by_key(|error| error.line);
+
+    match mode {
+        ParseMode::Strict if !errors.is_empty() => Err(errors),
+        ParseMode::Strict => Ok(parsed),
+        ParseMode::Lenient => {
+            parsed.diagnostics = errors;
+            Ok(parsed)
+        }
+    }
+}
+
+/// Reads all valid rules from the contents of a Public Suffix List file,
+/// skipping malformed entries.
+///
+/// # Arguments
+///
+/// * `data` - The contents of a Public Suffix List file
+///
+/// # Returns
+///
+/// * `Vec<Rule>` - The rules in the order they appear in the file
+pub fn parse_rules(data: &str) -> Vec<Rule> {
+    match parse_list(data, ParseMode::Lenient) {
+        Ok(parsed) => parsed.rules,
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Checks a single rule and returns its kind and its normalized form.
+fn validate_rule(text: &str) -> Result<(RuleKind, String), PslErrorKind> {
+    let (kind, body) = if let Some(body) = text.strip_prefix('!') {
+        (RuleKind::Exception, body)
+    } else if let Some(body) = text.strip_prefix("*.") {
+        (RuleKind::Wildcard, body)
+    } else {
+        (RuleKind::Normal, text)
+    };
+
+    for label in body.split('.') {
+        if label.is_empty() {
+            return Err(PslErrorKind::EmptyLabel);
+        }
+        if label.contains('*') {
+            return Err(PslErrorKind::MisplacedWildcard);
+        }
+        if let Some(c) = label
+            .chars()
+            .find(|&c| c.is_ascii() && !c.is_ascii_alphanumeric() && c != '-')
+        {
+            return Err(PslErrorKind::InvalidCharacter(c));
+        }
+    }
+
+    // An exception needs a parent to override
+    if kind == RuleKind::Exception && !body.contains('.') {
+        return Err(PslErrorKind::ExceptionWithoutWildcard);
+    }
+
+    if idna::domain_to_ascii(body).is_err() {
+        return Err(PslErrorKind::InvalidIdn);
+    }
+
+    Ok((kind, normalize_rule(text)))
+}
+
+/// Lowercases a rule and converts its labels to punycode for comparison
+fn normalize_rule(text: &str) -> String {
+    text.split('.')
+        .map(|label| match idna::domain_to_ascii(label) {
+            Ok(ascii) if !ascii.is_empty() => ascii,
+            _ => label.to_lowercase(),
+        })
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+/// Reads a `NAME: value` header comment such as `// VERSION: ...`
+fn header_field(comment: &str, name: &str) -> Option<String> {
+    let value = comment.trim().strip_prefix(name)?.strip_prefix(':')?.trim();
+    if value.is_empty() {
+        return None;
+    }
+    Some(value.to_string())
+}
+
+/// Recognizes `===BEGIN ICANN DOMAINS===` style markers inside a comment.
+///
+/// Returns `Some(Some(section))` for a BEGIN marker, `Some(None)` for an END
+/// marker and `None` for any other comment.
+fn section_marker(comment: &str) -> Option<Option<Section>> {
+    let comment = comment.trim();
+    match comment {
+        "===BEGIN ICANN DOMAINS===" => Some(Some(Section::Icann)),
+        "===BEGIN PRIVATE DOMAINS===" => Some(Some(Section::Private)),
+        "===END ICANN DOMAINS===" | "===END PRIVATE DOMAINS===" => Some(None),
+        _ => None,
+    }
+}
@@ -0,0 +1,236 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::psl::{self, Section};
+use crate::suffix_store::SuffixStore;
+
+/// Rules added, removed or moved within one section of the list
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SectionDiff {
+    pub section: Section,
+    /// Rules present only in the new list
+    pub added: Vec<String>,
+    /// Rules present only in the old list
+    pub removed: Vec<String>,
+    /// Rules that moved into this section from another one
+    pub changed: Vec<String>,
+}
+
+/// A host whose root domain differs between two lists
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HostChange {
+    pub host: String,
+    pub old: Result<String, String>,
+    pub new: Result<String, String>,
+}
+
+/// The impact of replacing one Public Suffix List with another
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PslDiff {
+    /// Rule differences, one entry per section
+    pub sections: Vec<SectionDiff>,
+    /// Hosts whose root domain changes
+    pub hosts: Vec<HostChange>,
+}
+
+impl PslDiff {
+    /// Returns true if neither the rules nor any of the checked hosts changed
+    pub fn is_empty(&self) -> bool {
+        self.hosts.is_empty()
+            && self
+                .sections
+                .iter()
+                .all(|s| s.added.is_empty() && s.removed.is_empty() && s.changed.is_empty())
+    }
+}
+
+/// Compares the rules of two Public Suffix List files.
+///
+/// A rule is identified by its text in ASCII form, so `*.ck` and `!www.ck`
+/// are distinct rules, while `公司.cn` and `xn--55qx5d.cn` are the same rule
+/// and are not reported. Rules are reported as written in the list they are
+/// taken from. A rule that appears in both lists but in different sections
+/// is reported as changed in the section it was moved to.
+///
+/// # Arguments
+///
+/// * `old_data` - The contents of the currently used list
+/// * `new_data` - The contents of the candidate list
+///
+/// # Returns
+///
+/// * `Vec<SectionDiff>` - One entry per section, in list order, with sorted rules
+pub fn diff_rules(old_data: &str, new_data: &str) -> Vec<SectionDiff> {
+    let old_rules = rule_sections(old_data);
+    let new_rules = rule_sections(new_data);
+
+    let mut sections: Vec<SectionDiff> = [Section::Icann, Section::Private]
+        .into_iter()
+        .map(|section| SectionDiff {
+            section,
+            added: Vec::new(),
+            removed: Vec::new(),
+            changed: Vec::new(),
+        })
+        .collect();
+
+    for (key, (rule, section)) in &new_rules {
+        let diff = &mut sections[section_index(*section)];
+        match old_rules.get(key) {
+            None => diff.added.push(rule.clone()),
+            Some((_, old_section)) if old_section != section => diff.changed.push(rule.clone()),
+            Some(_) => {}
+        }
+    }
+
+    for (key, (rule, section)) in &old_rules {
+        if !new_rules.contains_key(key) {
+            sections[section_index(*section)].removed.push(rule.clone());
+        }
+    }
+
+    for diff in &mut sections {
+        diff.added.sort();
+        diff.removed.sort();
+        diff.changed.sort();
+    }
+    sections
+}
+
+/// Finds the hosts whose root domain differs between two stores.
+///
+/// A host that fails to resolve with one list but not the other, or that
+/// fails with different errors, is reported as well.
+///
+/// # Arguments
+///
+/// * `old` - A store loaded from the currently used list
+/// * `new` - A store loaded from the candidate list
+/// * `hosts` - The hosts to check
+///
+/// # Returns
+///
+/// * `Vec<HostChange>` - The affected hosts, in input order
+pub fn diff_hosts<'a, I>(old: &SuffixStore, new: &SuffixStore, hosts: I) -> Vec<HostChange>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    hosts
+        .into_iter()
+        .filter_map(|host| {
+            let old_root = old.extract_root_domain(host);
+            let new_root = new.extract_root_domain(host);
+            if old_root == new_root {
+                return None;
+            }
+            Some(HostChange {
+                host: host.to_string(),
+                old: old_root,
+                new: new_root,
+            })
+        })
+        .collect()
+}
+
+/// Compares two Public Suffix List files and reports the impact on a set of hosts.
+///
+/// # Arguments
+///
+/// * `old_data` - The contents of the currently used list
+/// * `new_data` - The contents of the candidate list
+/// * `hosts` - The hosts to check for root domain changes
+///
+/// # Returns
+///
+/// * `Ok(PslDiff)` - The rule and host differences
+/// * `Err(String)` - An error message if either list cannot be parsed
+pub fn diff_lists<'a, I>(old_data: &str, new_data: &str, hosts: I) -> Result<PslDiff, String>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    let old: SuffixStore = old_data.parse()?;
+    let new: SuffixStore = new_data.parse()?;
+
+    Ok(PslDiff {
+        sections: diff_rules(old_data, new_data),
+        hosts: diff_hosts(&old, &new, hosts),
+    })
+}
+
+/// Maps the ASCII form of each rule to its text and section, keeping the
+/// first one a rule appears as
+fn rule_sections(data: &str) -> BTreeMap<String, (String, Section)> {
+    let mut rules = BTreeMap::new();
+    for rule in psl::parse_rules(data) {
+        rules.entry(rule_key(&rule.text)).or_insert((rule.text, rule.section));
+    }
+    rules
+}
+
+/// Converts a rule to ASCII form, as [`crate::suffix_trie::SuffixTrie::compile`]
+/// does, keeping its `!` or `*.` prefix. A rule that does not convert is
+/// kept as written.
+fn rule_key(text: &str) -> String {
+    let (prefix, body) = match text.strip_prefix('!') {
+        Some(body) => ("!", body),
+        None => match text.strip_prefix("*.") {
+            Some(body) => ("*.", body),
+            None => ("", text),
+        },
+    };
+    match idna::domain_to_ascii(body) {
+        Ok(ascii) => format!("{}{}", prefix, ascii),
+        Err(_) => text.to_string(),
+    }
+}
+
+fn section_index(section: Section) -> usize {
+    match section {
+        Section::Icann => 0,
+        Section::Private => 1,
+    }
+}
+
+impl fmt::Display for PslDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for section in &self.sections {
+            writeln!(
+                f,
+                "{} rules: {} added, {} removed, {} changed",
+                section.section,
+                section.added.len(),
+                section.removed.len(),
+                section.changed.len()
+            )?;
+            for rule in &section.added {
+                writeln!(f, "  + {}", rule)?;
+            }
+            for rule in &section.removed {
+                writeln!(f, "  - {}", rule)?;
+            }
+            for rule in &section.changed {
+                writeln!(f, "  ~ {}", rule)?;
+            }
+        }
+
+        writeln!(f, "Affected hosts: {}", self.hosts.len())?;
+        for change in &self.hosts {
+            writeln!(
+                f,
+                "  {}: {} -> {}",
+                change.host,
+                display_result(&change.old),
+                display_result(&change.new)
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+fn display_result(result: &Result<String, String>) -> String {
+    match result {
+        Ok(domain) => domain.clone(),
+        Err(e) => format!("error ({})", e),
+    }
+}
@@ -0,0 +1,209 @@
+use pyo3::exceptions::{PyTypeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyFloat, PyString};
+
+use crate::domain_parser;
+use crate::suffix_store::SuffixStore;
+
+/// A loaded Public Suffix List, exposed to Python as `domain_validator.SuffixStore`
+#[pyclass(name = "SuffixStore", module = "domain_validator", frozen)]
+pub struct PySuffixStore {
+    store: SuffixStore,
+}
+
+#[pymethods]
+impl PySuffixStore {
+    /// Loads a store from a `public_suffix_list.dat` file, or uses the embedded
+    /// list when no path is given.
+    #[new]
+    #[pyo3(signature = (path=None))]
+    fn new(path: Option<&str>) -> PyResult<Self> {
+        let store = match path {
+            Some(path) => SuffixStore::from_file(path).map_err(PyValueError::new_err)?,
+            None => SuffixStore::embedded(),
+        };
+        Ok(PySuffixStore { store })
+    }
+
+    /// Loads a store from a binary trie written by `domain-validator psl-compile`.
+    #[staticmethod]
+    fn from_compiled_file(path: &str) -> PyResult<Self> {
+        let store = SuffixStore::from_compiled_file(path).map_err(PyValueError::new_err)?;
+        Ok(PySuffixStore { store })
+    }
+
+    /// Parses a store from the text of a Public Suffix List.
+    #[staticmethod]
+    fn from_string(data: &str) -> PyResult<Self> {
+        let store = data.parse().map_err(PyValueError::new_err)?;
+        Ok(PySuffixStore { store })
+    }
+
+    /// Metadata of the loaded list as a dict.
+    #[getter]
+    fn metadata<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let metadata = self.store.metadata();
+        let dict = PyDict::new(py);
+        dict.set_item("version", metadata.version.as_deref())?;
+        dict.set_item("commit", metadata.commit.as_deref())?;
+        dict.set_item("icann_rules", metadata.icann_rules)?;
+        dict.set_item("private_rules", metadata.private_rules)?;
+        dict.set_item("loaded_at", metadata.loaded_at)?;
+        dict.set_item("source", &metadata.source)?;
+        Ok(dict)
+    }
+
+    /// Extracts the root domain (eTLD+1) of a domain, raising `ValueError` on failure.
+    fn extract_root_domain(&self, domain: &str) -> PyResult<String> {
+        self.store.extract_root_domain(domain).map_err(PyValueError::new_err)
+    }
+
+    /// Extracts the root domain (eTLD+1) of a URL, raising `ValueError` on failure.
+    fn extract_root_domain_from_url(&self, url: &str) -> PyResult<String> {
+        self.store.extract_root_domain_from_url(url).map_err(PyValueError::new_err)
+    }
+
+    /// Splits a host into `suffix`, `registrable_domain`, `subdomain` and `section`.
+    fn split_host<'py>(&self, py: Python<'py>, host: &str) -> PyResult<Bound<'py, PyDict>> {
+        let parts = self.store.split_host(host).map_err(PyValueError::new_err)?;
+        let dict = PyDict::new(py);
+        dict.set_item("suffix", parts.suffix.as_ref())?;
+        dict.set_item("registrable_domain", parts.registrable_domain.as_deref())?;
+        dict.set_item("subdomain", parts.subdomain.as_deref())?;
+        dict.set_item("section", parts.section.map(|section| section.to_string().to_lowercase()))?;
+        Ok(dict)
+    }
+
+    /// Vectorized `extract_root_domain`; see the module-level function.
+    #[pyo3(signature = (domains, strict=false))]
+    fn extract_root_domains(
+        &self,
+        py: Python<'_>,
+        domains: &Bound<'_, PyAny>,
+        strict: bool,
+    ) -> PyResult<Vec<Option<String>>> {
+        map_values(py, domains, strict, |domain| self.store.extract_root_domain(domain))
+    }
+
+    /// Vectorized `extract_root_domain_from_url`; see the module-level function.
+    #[pyo3(signature = (urls, strict=false))]
+    fn extract_root_domains_from_urls(
+        &self,
+        py: Python<'_>,
+        urls: &Bound<'_, PyAny>,
+        strict: bool,
+    ) -> PyResult<Vec<Option<String>>> {
+        map_values(py, urls, strict, |url| self.store.extract_root_domain_from_url(url))
+    }
+
+    fn __repr__(&self) -> String {
+        let metadata = self.store.metadata();
+        format!(
+            "SuffixStore(source={:?}, version={:?}, rules={})",
+            metadata.source,
+            metadata.version.as_deref().unwrap_or("unknown"),
+            metadata.icann_rules + metadata.private_rules
+        )
+    }
+}
+
+/// Extracts the normalized host of a URL, raising `ValueError` on failure.
+#[pyfunction]
+fn parse_url(url: &str) -> PyResult<String> {
+    domain_parser::parse_url(url).map_err(PyValueError::new_err)
+}
+
+/// Extracts the root domain (eTLD+1) of a domain using the default list.
+#[pyfunction]
+fn extract_root_domain(domain: &str) -> PyResult<String> {
+    domain_parser::extract_root_domain(domain).map_err(PyValueError::new_err)
+}
+
+/// Extracts the root domain (eTLD+1) of a URL using the default list.
+#[pyfunction]
+fn extract_root_domain_from_url(url: &str) -> PyResult<String> {
+    domain_parser::extract_root_domain_from_url(url).map_err(PyValueError::new_err)
+}
+
+/// Extracts the root domains of an iterable of domains using the default list.
+///
+/// Returns a list of the same length. Missing values (`None` or NaN, as in a
+/// pandas column) and invalid domains give `None`, unless `strict` is set, in
+/// which case the first invalid domain raises `ValueError`.
+#[pyfunction]
+#[pyo3(signature = (domains, strict=false))]
+fn extract_root_domains(py: Python<'_>, domains: &Bound<'_, PyAny>, strict: bool) -> PyResult<Vec<Option<String>>> {
+    let store = SuffixStore::global().map_err(PyValueError::new_err)?;
+    map_values(py, domains, strict, |domain| store.extract_root_domain(domain))
+}
+
+/// Extracts the root domains of an iterable of URLs using the default list.
+///
+/// See `extract_root_domains` for the handling of missing and invalid values.
+#[pyfunction]
+#[pyo3(signature = (urls, strict=false))]
+fn extract_root_domains_from_urls(py: Python<'_>, urls: &Bound<'_, PyAny>, strict: bool) -> PyResult<Vec<Option<String>>> {
+    let store = SuffixStore::global().map_err(PyValueError::new_err)?;
+    map_values(py, urls, strict, |url| store.extract_root_domain_from_url(url))
+}
+
+/// Applies `f` to every string of a Python iterable without holding the GIL
+///
+/// # Arguments
+///
+/// * `values` - Any iterable of `str`, `None` or NaN
+/// * `strict` - Raise `ValueError` for the first failing value instead of returning `None`
+/// * `f` - The extraction to apply
+fn map_values<F>(py: Python<'_>, values: &Bound<'_, PyAny>, strict: bool, f: F) -> PyResult<Vec<Option<String>>>
+where
+    F: Fn(&str) -> Result<String, String> + Sync,
+{
+    if values.is_instance_of::<PyString>() {
+        return Err(PyTypeError::new_err("expected an iterable of strings, not a single str"));
+    }
+
+    let mut inputs = Vec::new();
+    for (index, value) in values.try_iter()?.enumerate() {
+        let value = value?;
+        if is_missing(&value) {
+            inputs.push(None);
+        } else if let Ok(value) = value.cast::<PyString>() {
+            inputs.push(Some(value.to_str()?.to_string()));
+        } else {
+            return Err(PyTypeError::new_err(format!(
+                "item {}: expected str or None, got {}",
+                index,
+                value.get_type().name()?
+            )));
+        }
+    }
+
+    py.detach(|| {
+        inputs
+            .iter()
+            .enumerate()
+            .map(|(index, input)| match input.as_deref().map(&f) {
+                None => Ok(None),
+                Some(Ok(root)) => Ok(Some(root)),
+                Some(Err(e)) if strict => Err(PyValueError::new_err(format!("item {}: {}", index, e))),
+                Some(Err(_)) => Ok(None),
+            })
+            .collect()
+    })
+}
+
+fn is_missing(value: &Bound<'_, PyAny>) -> bool {
+    value.is_none() || value.cast::<PyFloat>().is_ok_and(|float| float.value().is_nan())
+}
+
+/// The `domain_validator` Python module
+#[pymodule]
+fn domain_validator(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PySuffixStore>()?;
+    m.add_function(wrap_pyfunction!(parse_url, m)?)?;
+    m.add_function(wrap_pyfunction!(extract_root_domain, m)?)?;
+    m.add_function(wrap_pyfunction!(extract_root_domain_from_url, m)?)?;
+    m.add_function(wrap_pyfunction!(extract_root_domains, m)?)?;
+    m.add_function(wrap_pyfunction!(extract_root_domains_from_urls, m)?)?;
+    Ok(())
+}
@@ -0,0 +1,377 @@
+use base64::alphabet;
+use base64::engine::general_purpose::{GeneralPurpose, GeneralPurposeConfig};
+use base64::engine::DecodePaddingMode;
+use base64::Engine;
+use percent_encoding::percent_decode_str;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use url::Url;
+
+use crate::domain_parser;
+use crate::suffix_store::SuffixStore;
+
+/// Most URLs in a chain, so wrappers pointing at each other cannot loop forever
+const MAX_HOPS: usize = 10;
+
+/// Query parameters that generic redirectors put their target in
+const GENERIC_PARAMS: &[&str] = &["url", "redirect", "redirect_url", "target", "dest", "destination"];
+
+/// Characters giving the length of a run of replaced characters in ProofPoint
+/// v3 URLs, the first one standing for 2
+const PROOFPOINT_RUN_LENGTHS: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+/// Base64 as used by ProofPoint v3 URLs: URL-safe, with or without padding
+const PROOFPOINT_BASE64: GeneralPurpose = GeneralPurpose::new(
+    &alphabet::URL_SAFE,
+    GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent),
+);
+
+/// How a rule finds the target in a wrapper URL
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum Target {
+    /// The first of these query parameters holding an absolute `http` or
+    /// `https` URL
+    Query { params: Vec<String> },
+    /// ProofPoint URL Defense v2: the `u` parameter, with `-` standing for
+    /// `%` and `_` for `/`
+    ProofpointV2,
+    /// ProofPoint URL Defense v3: the URL between `/v3/__` and `__;` in the
+    /// path, with `*` standing for characters encoded after it
+    ProofpointV3,
+}
+
+/// A rule recognizing one kind of wrapper URL
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UnwrapRule {
+    /// The name reported in chains, e.g. `outlook-safelinks`
+    pub name: String,
+    /// The hosts the rule applies to: `*` for any host, `name.*` for the
+    /// domains registered as `name` under any public suffix (`google.*`
+    /// matches `www.google.co.uk`), or a domain and its subdomains
+    pub host: String,
+    /// The path the rule applies to, exactly or as a prefix when it ends
+    /// with `/`; `None` for any path
+    pub path: Option<String>,
+    /// Where the target is
+    pub target: Target,
+}
+
+/// One URL of a redirect chain
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Hop {
+    /// The URL
+    pub url: String,
+    /// Its normalized host
+    pub host: String,
+    /// The root domain of the host, if it has one
+    pub root_domain: Option<String>,
+    /// The rule that found the next URL in this one, `None` for the destination
+    pub rule: Option<String>,
+}
+
+/// A URL of a chain, parsed once
+struct WebUrl {
+    /// The URL as written
+    text: String,
+    url: Url,
+    /// Its normalized host
+    host: String,
+}
+
+/// A set of rules to unwrap redirect and safelink URLs to their destination
+#[derive(Debug, Clone, Default)]
+pub struct Unwrapper {
+    /// Rules in the order they are tried
+    rules: Vec<UnwrapRule>,
+}
+
+impl UnwrapRule {
+    /// Creates a rule that takes the target from query parameters.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name reported in chains
+    /// * `host` - The hosts the rule applies to, see [`UnwrapRule::host`]
+    /// * `path` - The path the rule applies to, see [`UnwrapRule::path`]
+    /// * `params` - The query parameters that may hold the target, in order
+    pub fn query(name: &str, host: &str, path: Option<&str>, params: &[&str]) -> Self {
+        Self {
+            name: name.to_string(),
+            host: host.to_ascii_lowercase(),
+            path: path.map(str::to_string),
+            target: Target::Query {
+                params: params.iter().map(|param| param.to_string()).collect(),
+            },
+        }
+    }
+
+    /// Creates a rule following the `redirect_uri` parameter of OAuth
+    /// authorization URLs on any host, named `oauth-redirect`.
+    ///
+    /// It is not one of the built-in rules: an authorization URL leads to
+    /// a login page, and only reaches its `redirect_uri` once the user has
+    /// signed in, so most callers want to stop at the login page. Add it
+    /// with [`Unwrapper::add_rule`] to follow the redirect as well.
+    pub fn oauth_redirect() -> Self {
+        Self::query("oauth-redirect", "*", None, &["redirect_uri"])
+    }
+
+    /// Returns whether the rule applies to a URL
+    fn matches(&self, store: &SuffixStore, url: &Url) -> bool {
+        let Some(host) = url.host_str() else {
+            return false;
+        };
+
+        let host_matches = if self.host == "*" {
+            true
+        } else if let Some(name) = self.host.strip_suffix(".*") {
+            store
+                .trie()
+                .registrable_domain(host)
+                .and_then(|domain| domain.split_once('.'))
+                .is_some_and(|(label, _)| label == name)
+        } else {
+            host == self.host || host.strip_suffix(self.host.as_str()).is_some_and(|rest| rest.ends_with('.'))
+        };
+
+        let path_matches = match self.path.as_deref() {
+            None => true,
+            Some(path) if path.ends_with('/') => url.path().starts_with(path),
+            Some(path) => url.path() == path,
+        };
+
+        host_matches && path_matches
+    }
+
+    /// Extracts the target of a URL the rule applies to, if it holds one.
+    /// `url_str` is the URL as written, which `url` was parsed from.
+    fn target(&self, url: &Url, url_str: &str) -> Option<WebUrl> {
+        let query_value = |name: &str| {
+            url.query_pairs()
+                .find(|(key, _)| key == name)
+                .map(|(_, value)| value.into_owned())
+        };
+
+        let target = match &self.target {
+            Target::Query { params } => {
+                return params
+                    .iter()
+                    .filter_map(|param| query_value(param))
+                    .find_map(web_url);
+            }
+            Target::ProofpointV2 => {
+                let value = query_value("u")?.replace('-', "%").replace('_', "/");
+                percent_decode_str(&value).decode_utf8().ok()?.into_owned()
+            }
+            // The URL parser would resolve dot segments in the embedded URL
+            // and encode its characters, so it is decoded as written
+            Target::ProofpointV3 => decode_proofpoint_v3(url_str)?,
+        };
+
+        web_url(target)
+    }
+}
+
+impl Unwrapper {
+    /// Creates an unwrapper without any rules
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates an unwrapper with the built-in rules, for Google
+    /// (`google.com/url?q=`), Outlook safelinks, Facebook (`l.php?u=`),
+    /// ProofPoint URL Defense v1 to v3 and, last, generic redirect
+    /// parameters such as `?redirect=` and `?url=` on any host. OAuth
+    /// `redirect_uri` parameters are not followed unless
+    /// [`UnwrapRule::oauth_redirect`] is added.
+    pub fn builtin() -> Self {
+        Self {
+            rules: vec![
+                UnwrapRule::query("google", "google.*", Some("/url"), &["q", "url"]),
+                UnwrapRule::query("outlook-safelinks", "safelinks.protection.outlook.com", None, &["url"]),
+                UnwrapRule::query("facebook", "facebook.com", Some("/l.php"), &["u"]),
+                UnwrapRule::query("proofpoint-v1", "urldefense.proofpoint.com", Some("/v1/url"), &["u"]),
+                UnwrapRule {
+                    name: "proofpoint-v2".to_string(),
+                    host: "urldefense.proofpoint.com".to_string(),
+                    path: Some("/v2/url".to_string()),
+                    target: Target::ProofpointV2,
+                },
+                UnwrapRule {
+                    name: "proofpoint-v3".to_string(),
+                    host: "urldefense.com".to_string(),
+                    path: Some("/v3/".to_string()),
+                    target: Target::ProofpointV3,
+                },
+                UnwrapRule::query("generic", "*", None, GENERIC_PARAMS),
+            ],
+        }
+    }
+
+    /// Returns the rules in the order they are tried
+    pub fn rules(&self) -> &[UnwrapRule] {
+        &self.rules
+    }
+
+    /// Adds a rule, tried before the rules already present so it can
+    /// override them
+    pub fn add_rule(&mut self, rule: UnwrapRule) {
+        self.rules.insert(0, rule);
+    }
+
+    /// Loads rules from a file, tried before the rules already present.
+    ///
+    /// Each line holds a rule name, a host pattern, a path (`*` for any) and
+    /// comma-separated query parameters, separated by whitespace:
+    ///
+    /// ```text
+    /// # name   host                path   parameters
+    /// mycorp   gateway.mycorp.com  /r     target,to
+    /// ```
+    ///
+    /// Blank lines and lines starting with `#` are ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the file
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - The rules were added
+    /// * `Err(String)` - An error message if the file cannot be read or has an
+    ///   invalid line, with its line number
+    pub fn load_rules<P: AsRef<Path>>(&mut self, path: P) -> Result<(), String> {
+        let path = path.as_ref();
+        let data = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read unwrap rules from {}: {}", path.display(), e))?;
+
+        let mut rules = Vec::new();
+        for (index, line) in data.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let [name, host, rule_path, params] = fields[..] else {
+                return Err(format!(
+                    "{}:{}: expected a name, host, path and parameters, found '{}'",
+                    path.display(),
+                    index + 1,
+                    line
+                ));
+            };
+            let params: Vec<&str> = params.split(',').filter(|param| !param.is_empty()).collect();
+            let rule_path = (rule_path != "*").then_some(rule_path);
+            rules.push(UnwrapRule::query(name, host, rule_path, &params));
+        }
+
+        self.rules.splice(0..0, rules);
+        Ok(())
+    }
+
+    /// Follows a URL through redirect and safelink wrappers to its destination.
+    ///
+    /// The first rule that applies to a URL and finds a target in it gives
+    /// the next URL, until no rule applies, a URL repeats or the chain has
+    /// 10 URLs.
+    ///
+    /// # Arguments
+    ///
+    /// * `store` - The Public Suffix List used to find root domains
+    /// * `url_str` - The URL to unwrap, e.g. `https://www.google.com/url?q=https://example.com/`
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Vec<Hop>)` - Every URL from `url_str` to the destination, which is last
+    /// * `Err(String)` - An error message if `url_str` is invalid
+    pub fn unwrap_url(&self, store: &SuffixStore, url_str: &str) -> Result<Vec<Hop>, String> {
+        let mut chain: Vec<Hop> = Vec::new();
+        let mut seen = HashSet::new();
+        let (url, host, _) = domain_parser::parse_url_parts(url_str)?;
+        let mut current = WebUrl {
+            text: url_str.to_string(),
+            url,
+            host,
+        };
+
+        loop {
+            let root_domain = store.extract_root_domain(&current.host).ok();
+            seen.insert(current.text.clone());
+
+            let next = (chain.len() + 1 < MAX_HOPS)
+                .then(|| self.find_target(store, &current))
+                .flatten()
+                .filter(|(_, target)| !seen.contains(&target.text));
+
+            chain.push(Hop {
+                url: current.text,
+                host: current.host,
+                root_domain,
+                rule: next.as_ref().map(|(rule, _)| rule.clone()),
+            });
+            match next {
+                Some((_, target)) => current = target,
+                None => return Ok(chain),
+            }
+        }
+    }
+
+    /// Finds the first rule with a target in a URL, returning its name and the target
+    fn find_target(&self, store: &SuffixStore, current: &WebUrl) -> Option<(String, WebUrl)> {
+        self.rules
+            .iter()
+            .filter(|rule| rule.matches(store, &current.url))
+            .find_map(|rule| Some((rule.name.clone(), rule.target(&current.url, &current.text)?)))
+    }
+}
+
+/// Parses a target, if it is an absolute `http` or `https` URL with a domain host
+fn web_url(target: String) -> Option<WebUrl> {
+    let (url, host, scheme_added) = domain_parser::parse_url_parts(&target).ok()?;
+    (!scheme_added && matches!(url.scheme(), "http" | "https")).then_some(WebUrl {
+        text: target,
+        url,
+        host,
+    })
+}
+
+/// Decodes the target of a ProofPoint v3 URL, e.g.
+/// `https://urldefense.com/v3/__https://example.com/a*b__;Kw!!token`
+fn decode_proofpoint_v3(url: &str) -> Option<String> {
+    let (_, rest) = url.split_once("/v3/__")?;
+    let (encoded_url, rest) = rest.split_once("__;")?;
+    if !encoded_url.contains('*') {
+        return Some(encoded_url.to_string());
+    }
+
+    // The characters replaced by `*` follow, base64 encoded, up to a `!`
+    let encoded_chars = rest.split('!').next().unwrap_or_default();
+    let bytes = PROOFPOINT_BASE64.decode(encoded_chars).ok()?;
+    let replaced = String::from_utf8(bytes).ok()?;
+    let mut replaced = replaced.chars();
+
+    // A `*` stands for one character, `**` and a length character for a run
+    let mut target = String::with_capacity(encoded_url.len());
+    let mut chars = encoded_url.chars();
+    while let Some(c) = chars.next() {
+        if c != '*' {
+            target.push(c);
+            continue;
+        }
+        let run = match chars.clone().next() {
+            Some('*') => {
+                chars.next();
+                PROOFPOINT_RUN_LENGTHS.find(chars.next()?)? + 2
+            }
+            _ => 1,
+        };
+        for _ in 0..run {
+            target.push(replaced.next()?);
+        }
+    }
+    Some(target)
+}
@@ -0,0 +1,617 @@
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::OnceLock;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::warn;
+
+use crate::domain_parser::{self, NO_ROOT_DOMAIN_ERROR, PSL_PATH};
+use crate::idn_display::{self, DisplayDecision};
+use crate::idna_options::{self, IdnaOptions};
+use crate::psl::{self, ParseMode, Section};
+use crate::suffix_trie::{last_labels_start, SuffixTrie};
+
+/// The Public Suffix List bundled into the binary at compile time
+const EMBEDDED_PSL: &str = include_str!("../public_suffix_list.dat");
+
+/// Source name reported for the bundled list
+pub const EMBEDDED_SOURCE: &str = "embedded";
+
+/// Source name reported for lists parsed from an in-memory string
+pub const INLINE_SOURCE: &str = "inline";
+
+/// The store shared by the free functions in [`crate::domain_parser`]
+static DEFAULT_STORE: OnceLock<Result<SuffixStore, String>> = OnceLock::new();
+
+/// Information about the Public Suffix List loaded into a store
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PslMetadata {
+    /// The `// VERSION:` header of the list (e.g. `2025-02-27_07-53-30_UTC`)
+    pub version: Option<String>,
+    /// The `// COMMIT:` header of the list
+    pub commit: Option<String>,
+    /// Number of rules in the ICANN section
+    pub icann_rules: usize,
+    /// Number of rules in the PRIVATE section
+    pub private_rules: usize,
+    /// When the list was loaded, in seconds since the Unix epoch
+    pub loaded_at: u64,
+    /// The file path the list was loaded from, `embedded` or `inline`
+    pub source: String,
+}
+
+impl PslMetadata {
+    /// Returns when the list was published, in seconds since the Unix epoch.
+    ///
+    /// The time is read from the `VERSION` header, which has the form
+    /// `YYYY-MM-DD_HH-MM-SS_UTC`. Returns `None` if the header is missing or
+    /// does not have that form.
+    pub fn published_at(&self) -> Option<u64> {
+        parse_version_timestamp(self.version.as_deref()?)
+    }
+
+    /// Returns how old the list was at the given time, based on its `VERSION` header
+    pub fn age_at(&self, now: SystemTime) -> Option<Duration> {
+        let published = UNIX_EPOCH + Duration::from_secs(self.published_at()?);
+        Some(now.duration_since(published).unwrap_or_default())
+    }
+}
+
+/// A host split into its public suffix, registrable domain and subdomain.
+///
+/// The parts borrow from the host they were split from whenever possible and
+/// only own their data when the host had to be converted to punycode first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DomainParts<'a> {
+    /// The public suffix, e.g. `co.uk`
+    pub suffix: Cow<'a, str>,
+    /// The registrable domain (eTLD+1), e.g. `example.co.uk`, or `None` if the
+    /// host is itself a public suffix
+    pub registrable_domain: Option<Cow<'a, str>>,
+    /// Everything left of the registrable domain, e.g. `www`, or `None` if empty
+    pub subdomain: Option<Cow<'a, str>>,
+    /// Section of the rule that matched, or `None` if only the implicit `*` rule matched
+    pub section: Option<Section>,
+}
+
+impl DomainParts<'_> {
+    /// Returns true if every part borrows from the input host
+    pub fn is_borrowed(&self) -> bool {
+        let borrowed = |part: &Cow<'_, str>| matches!(part, Cow::Borrowed(_));
+        borrowed(&self.suffix)
+            && self.registrable_domain.as_ref().is_none_or(borrowed)
+            && self.subdomain.as_ref().is_none_or(borrowed)
+    }
+
+    /// Converts the parts into owned data, detaching them from the input host
+    pub fn into_owned(self) -> DomainParts<'static> {
+        DomainParts {
+            suffix: Cow::Owned(self.suffix.into_owned()),
+            registrable_domain: self.registrable_domain.map(|d| Cow::Owned(d.into_owned())),
+            subdomain: self.subdomain.map(|s| Cow::Owned(s.into_owned())),
+            section: self.section,
+        }
+    }
+}
+
+/// Everything known about the host of a URL, as reported by the command-line tool
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct UrlBreakdown {
+    /// The normalized host, e.g. `www.example.co.uk`
+    pub host: String,
+    /// The root domain (eTLD+1), e.g. `example.co.uk`
+    pub root_domain: String,
+    /// The public suffix, e.g. `co.uk`
+    pub suffix: String,
+    /// Everything left of the root domain, e.g. `www`, or `None` if empty
+    pub subdomain: Option<String>,
+    /// Section of the rule that matched, or `None` if only the implicit `*` rule matched
+    pub section: Option<Section>,
+}
+
+/// A loaded Public Suffix List that root domains can be extracted against.
+///
+/// Loading and parsing the list is the expensive part of root domain
+/// extraction, so a store can be created once and reused for many lookups.
+/// The rules are held as a [`SuffixTrie`], which can also be loaded directly
+/// from its compiled binary form to skip parsing entirely.
+pub struct SuffixStore {
+    trie: SuffixTrie<'static>,
+    metadata: PslMetadata,
+    idna_options: IdnaOptions,
+    /// The plain ICANN rules, sorted, built on first use
+    icann_suffixes: OnceLock<Vec<String>>,
+}
+
+impl SuffixStore {
+    /// Loads a store from a Public Suffix List file on disk.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path to a `public_suffix_list.dat` style file
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(SuffixStore)` - The loaded store
+    /// * `Err(String)` - An error message if the file cannot be read or parsed
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let path = path.as_ref();
+        let data = match fs::read_to_string(path) {
+            Ok(data) => data,
+            Err(_) => return Err("Failed to load Public Suffix List".to_string()),
+        };
+
+        Self::from_data(&data, path.display().to_string())
+    }
+
+    /// Loads a store from a compiled suffix trie file on disk.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path to a file produced by [`SuffixTrie::as_bytes`]
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(SuffixStore)` - The loaded store
+    /// * `Err(String)` - An error message if the file cannot be read or is not a valid trie
+    pub fn from_compiled_file<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let path = path.as_ref();
+        let data = match fs::read(path) {
+            Ok(data) => data,
+            Err(_) => return Err("Failed to load compiled suffix trie".to_string()),
+        };
+
+        Self::from_compiled(data, path.display().to_string())
+    }
+
+    /// Creates a store from a compiled suffix trie, e.g. one embedded with `include_bytes!`.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - The encoded trie, either `&'static [u8]` or `Vec<u8>`
+    /// * `source` - The name reported as the list source in the metadata
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(SuffixStore)` - The loaded store
+    /// * `Err(String)` - An error message if the data is not a valid trie
+    pub fn from_compiled<D: Into<Cow<'static, [u8]>>>(data: D, source: String) -> Result<Self, String> {
+        let trie = SuffixTrie::from_bytes(data)?;
+        Ok(Self::from_trie(trie, source))
+    }
+
+    /// Creates a store from the Public Suffix List bundled into the binary.
+    pub fn embedded() -> Self {
+        Self::from_data(EMBEDDED_PSL, EMBEDDED_SOURCE.to_string())
+            .expect("the embedded Public Suffix List is valid")
+    }
+
+    /// Loads the default store: `public_suffix_list.dat` from the working
+    /// directory if it exists, otherwise the embedded list.
+    ///
+    /// On WebAssembly there is no file system, so the embedded list is always used.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(SuffixStore)` - The loaded store
+    /// * `Err(String)` - An error message if the file exists but cannot be parsed
+    pub fn load_default() -> Result<Self, String> {
+        if cfg!(not(target_arch = "wasm32")) && Path::new(PSL_PATH).exists() {
+            Self::from_file(PSL_PATH)
+        } else {
+            Ok(Self::embedded())
+        }
+    }
+
+    /// Returns the process-wide default store, loading it with
+    /// [`Self::load_default`] on first use.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(&SuffixStore)` - The shared store
+    /// * `Err(String)` - The error from loading the default list, if it failed
+    pub fn global() -> Result<&'static Self, String> {
+        DEFAULT_STORE
+            .get_or_init(Self::load_default)
+            .as_ref()
+            .map_err(Clone::clone)
+    }
+
+    /// Returns information about the loaded list
+    pub fn metadata(&self) -> &PslMetadata {
+        &self.metadata
+    }
+
+    /// Returns the suffix rules of the loaded list
+    pub fn trie(&self) -> &SuffixTrie<'static> {
+        &self.trie
+    }
+
+    /// Returns the ICANN suffixes of the loaded list, without wildcard and
+    /// exception rules, sorted. They are collected from the trie on the
+    /// first call and reused afterwards.
+    pub(crate) fn icann_suffixes(&self) -> &[String] {
+        self.icann_suffixes.get_or_init(|| {
+            self.trie
+                .rules()
+                .into_iter()
+                .filter(|rule| rule.section == Section::Icann && !rule.text.starts_with(['*', '!']))
+                .map(|rule| rule.text)
+                .collect()
+        })
+    }
+
+    /// Sets the IDNA options used to convert domains to ASCII.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - The options used by every lookup on this store
+    ///
+    /// # Returns
+    ///
+    /// The store with the new options
+    pub fn with_idna_options(mut self, options: IdnaOptions) -> Self {
+        self.idna_options = options;
+        self
+    }
+
+    /// Returns the IDNA options used to convert domains to ASCII
+    pub fn idna_options(&self) -> &IdnaOptions {
+        &self.idna_options
+    }
+
+    /// Logs a warning if the loaded list is older than `max_age`.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_age` - The maximum acceptable age of the list
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - True if the list is stale or its age cannot be determined
+    pub fn warn_if_stale(&self, max_age: Duration) -> bool {
+        let source = &self.metadata.source;
+        match self.metadata.age_at(UNIX_EPOCH + Duration::from_secs(unix_now())) {
+            Some(age) if age > max_age => {
+                warn!(
+                    "Public Suffix List from {} (version {}) is {} days old, older than the maximum of {} days",
+                    source,
+                    self.metadata.version.as_deref().unwrap_or("unknown"),
+                    age.as_secs() / SECONDS_PER_DAY,
+                    max_age.as_secs() / SECONDS_PER_DAY
+                );
+                true
+            }
+            Some(_) => false,
+            None => {
+                warn!("Public Suffix List from {} has no usable VERSION header, its age is unknown", source);
+                true
+            }
+        }
+    }
+
+    /// Extracts the root domain (eTLD+1) from a domain string using this list.
+    ///
+    /// See [`crate::domain_parser::extract_root_domain`] for the details of
+    /// the normalization applied.
+    ///
+    /// # Arguments
+    ///
+    /// * `domain` - A string slice containing the domain to process
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(String)` - The root domain if successful
+    /// * `Err(String)` - An error message if extraction fails
+    pub fn extract_root_domain(&self, domain: &str) -> Result<String, String> {
+        self.extract_root_domain_with(domain, &self.idna_options)
+    }
+
+    /// Extracts the root domain (eTLD+1) from a domain string with the given
+    /// IDNA options instead of the store's own.
+    ///
+    /// # Arguments
+    ///
+    /// * `domain` - A string slice containing the domain to process
+    /// * `options` - How to convert the domain to ASCII
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(String)` - The root domain if successful
+    /// * `Err(String)` - An error message if extraction fails
+    pub fn extract_root_domain_with(&self, domain: &str, options: &IdnaOptions) -> Result<String, String> {
+        // A domain given in Unicode is returned in Unicode, if the display
+        // policy allows it
+        self.root_domain_for_display(domain, !domain.is_ascii(), options)
+    }
+
+    /// Extracts the root domain of a domain and decides how to display it.
+    ///
+    /// Unlike [`Self::extract_root_domain`], which keeps the form of its
+    /// input, this applies [`idn_display::display_root_domain`] whatever the
+    /// form of the input, and records why the root domain is shown in Unicode
+    /// or punycode.
+    ///
+    /// # Arguments
+    ///
+    /// * `domain` - A string slice containing the domain to process, in
+    ///   Unicode or punycode form
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(DisplayDecision)` - The root domain to display, and why
+    /// * `Err(String)` - An error message if extraction fails
+    pub fn display_root_domain(&self, domain: &str) -> Result<DisplayDecision, String> {
+        self.display_root_domain_with(domain, &self.idna_options)
+    }
+
+    /// Extracts the root domain of a domain and decides how to display it,
+    /// with the given IDNA options instead of the store's own.
+    ///
+    /// # Arguments
+    ///
+    /// * `domain` - A string slice containing the domain to process, in
+    ///   Unicode or punycode form
+    /// * `options` - How to convert the domain to ASCII
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(DisplayDecision)` - The root domain to display, and why
+    /// * `Err(String)` - An error message if extraction fails
+    pub fn display_root_domain_with(&self, domain: &str, options: &IdnaOptions) -> Result<DisplayDecision, String> {
+        let root_domain = self.extract_root_domain_with(domain, options)?;
+        Ok(idn_display::display_root_domain(&root_domain))
+    }
+
+    /// Splits a host into its public suffix, registrable domain and subdomain.
+    ///
+    /// This is the allocation-free counterpart of [`Self::extract_root_domain`]
+    /// for hot paths. An ASCII host is trusted to be already normalized (as
+    /// returned by [`crate::domain_parser::parse_url`]) and the parts are
+    /// slices of it; no IDN validation or case folding is done, although
+    /// suffix matching itself ignores ASCII case. Only a host containing
+    /// non-ASCII characters is converted to punycode, in which case the parts
+    /// own their data.
+    ///
+    /// # Arguments
+    ///
+    /// * `host` - The host to split, e.g. `www.example.co.uk`
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(DomainParts)` - The parts of the host
+    /// * `Err(String)` - An error message if the host is empty, has an empty
+    ///   label or is not a valid internationalized domain name
+    pub fn split_host<'h>(&self, host: &'h str) -> Result<DomainParts<'h>, String> {
+        if host.is_ascii() {
+            return match split_ascii(&self.trie, host) {
+                Some(parts) => Ok(parts),
+                None => Err(format!("{} from '{}'", NO_ROOT_DOMAIN_ERROR, host)),
+            };
+        }
+
+        let ascii = self.idna_options.to_ascii(host)?;
+        match split_ascii(&self.trie, &ascii) {
+            Some(parts) => Ok(parts.into_owned()),
+            None => Err(format!("{} from '{}'", NO_ROOT_DOMAIN_ERROR, host)),
+        }
+    }
+
+    /// Extracts the root domain from a URL string using this list.
+    ///
+    /// # Arguments
+    ///
+    /// * `url_str` - A string slice containing the URL to process
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(String)` - The root domain if successful
+    /// * `Err(String)` - An error message if extraction fails
+    pub fn extract_root_domain_from_url(&self, url_str: &str) -> Result<String, String> {
+        self.extract_root_domain_from_url_with(url_str, &self.idna_options)
+    }
+
+    /// Extracts the root domain from a URL string with the given IDNA options
+    /// instead of the store's own.
+    ///
+    /// # Arguments
+    ///
+    /// * `url_str` - A string slice containing the URL to process
+    /// * `options` - How to convert the host to ASCII
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(String)` - The root domain if successful
+    /// * `Err(String)` - An error message if extraction fails
+    pub fn extract_root_domain_from_url_with(&self, url_str: &str, options: &IdnaOptions) -> Result<String, String> {
+        // First parse the URL to get the host in punycode
+        let host = domain_parser::parse_url_with(url_str, options)?;
+
+        // Then extract the root domain from the host, in Unicode if the URL
+        // spelled it that way
+        self.root_domain_for_display(&host, spells_host_in_unicode(url_str, &host), options)
+    }
+
+    /// Breaks the host of a URL down into its root domain, suffix and subdomain.
+    ///
+    /// # Arguments
+    ///
+    /// * `url_str` - A string slice containing the URL to process
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(UrlBreakdown)` - The parts of the host if successful
+    /// * `Err(String)` - An error message if the URL is invalid or has no root domain
+    pub fn breakdown_url(&self, url_str: &str) -> Result<UrlBreakdown, String> {
+        let host = domain_parser::parse_url_with(url_str, &self.idna_options)?;
+        let root_domain = self.extract_root_domain(&host)?;
+        let parts = self.split_host(&host)?;
+
+        Ok(UrlBreakdown {
+            suffix: parts.suffix.into_owned(),
+            subdomain: parts.subdomain.map(Cow::into_owned),
+            section: parts.section,
+            host,
+            root_domain,
+        })
+    }
+
+    /// Extracts the root domain of `domain`, in the form it should be shown.
+    ///
+    /// A root domain requested in Unicode is returned in Unicode only when
+    /// [`idn_display::display_root_domain`] allows it, and in punycode
+    /// otherwise.
+    fn root_domain_for_display(
+        &self,
+        domain: &str,
+        unicode_requested: bool,
+        options: &IdnaOptions,
+    ) -> Result<String, String> {
+        let normalized_domain = options.to_ascii(domain)?;
+
+        match self.trie.registrable_domain(&normalized_domain) {
+            Some(root_domain) if unicode_requested => Ok(idn_display::display_root_domain(root_domain).host),
+            Some(root_domain) => Ok(root_domain.to_string()),
+            None => Err(format!("{} from '{}'", NO_ROOT_DOMAIN_ERROR, domain)),
+        }
+    }
+
+    /// Parses and validates list data, recording where it came from
+    fn from_data(data: &str, source: String) -> Result<Self, String> {
+        let parsed = match psl::parse_list(data, ParseMode::Strict) {
+            Ok(parsed) => parsed,
+            Err(errors) => {
+                let mut message = format!("Failed to parse Public Suffix List: {}", errors[0]);
+                if errors.len() > 1 {
+                    message.push_str(&format!(" (and {} more)", errors.len() - 1));
+                }
+                return Err(message);
+            }
+        };
+
+        if parsed.rules.is_empty() {
+            return Err("Failed to parse Public Suffix List: no rules found".to_string());
+        }
+
+        Ok(Self::from_trie(SuffixTrie::compile(&parsed)?, source))
+    }
+
+    /// Wraps a trie, reading the list metadata from its header
+    fn from_trie(trie: SuffixTrie<'static>, source: String) -> Self {
+        let (icann_rules, private_rules) = trie.rule_counts();
+        let metadata = PslMetadata {
+            version: trie.version().map(str::to_string),
+            commit: trie.commit().map(str::to_string),
+            icann_rules,
+            private_rules,
+            loaded_at: unix_now(),
+            source,
+        };
+
+        SuffixStore {
+            trie,
+            metadata,
+            idna_options: IdnaOptions::default(),
+            icann_suffixes: OnceLock::new(),
+        }
+    }
+}
+
+impl FromStr for SuffixStore {
+    type Err = String;
+
+    /// Parses a store from the contents of a Public Suffix List file.
+    ///
+    /// The list is validated with the native parser first, so malformed
+    /// entries are reported with their line number and the reason they were
+    /// rejected.
+    fn from_str(data: &str) -> Result<Self, Self::Err> {
+        Self::from_data(data, INLINE_SOURCE.to_string())
+    }
+}
+
+/// Returns whether the host of `url_str`, as written, spells an
+/// internationalized label of `host` (the punycode host parsed from it) in
+/// Unicode. The path, query and fragment are not looked at.
+fn spells_host_in_unicode(url_str: &str, host: &str) -> bool {
+    let Some(written) = domain_parser::host_as_written(url_str).filter(|written| !written.is_ascii()) else {
+        return false;
+    };
+
+    let lowercase = written.to_lowercase();
+    host.split('.')
+        .filter(|label| label.starts_with("xn--"))
+        .any(|label| lowercase.contains(&idna_options::to_unicode(label)))
+}
+
+/// Splits an ASCII host into borrowed parts
+fn split_ascii<'h>(trie: &SuffixTrie<'_>, host: &'h str) -> Option<DomainParts<'h>> {
+    let found = trie.find(host)?;
+    let suffix = &host[last_labels_start(host, found.labels)?..];
+    let registrable_domain = last_labels_start(host, found.labels + 1).map(|start| &host[start..]);
+
+    let subdomain = registrable_domain.and_then(|domain| {
+        let rest = &host[..host.len() - domain.len()];
+        rest.strip_suffix('.').filter(|rest| !rest.is_empty())
+    });
+
+    Some(DomainParts {
+        suffix: Cow::Borrowed(suffix),
+        registrable_domain: registrable_domain.map(Cow::Borrowed),
+        subdomain: subdomain.map(Cow::Borrowed),
+        section: found.section,
+    })
+}
+
+/// Returns the current time in seconds since the Unix epoch.
+///
+/// `SystemTime::now` panics on `wasm32-unknown-unknown`, so the JavaScript
+/// clock is used there when the `wasm` feature is enabled.
+#[cfg(not(target_arch = "wasm32"))]
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+fn unix_now() -> u64 {
+    (js_sys::Date::now() / 1000.0) as u64
+}
+
+#[cfg(all(target_arch = "wasm32", not(feature = "wasm")))]
+fn unix_now() -> u64 {
+    0
+}
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// Converts a `YYYY-MM-DD_HH-MM-SS_UTC` version string to Unix seconds
+fn parse_version_timestamp(version: &str) -> Option<u64> {
+    let version = version.strip_suffix("_UTC")?;
+    let (date, time) = version.split_once('_')?;
+
+    let mut date = date.splitn(3, '-').map(str::parse::<u64>);
+    let (year, month, day) = (date.next()?.ok()?, date.next()?.ok()?, date.next()?.ok()?);
+    let mut time = time.splitn(3, '-').map(str::parse::<u64>);
+    let (hour, minute, second) = (time.next()?.ok()?, time.next()?.ok()?, time.next()?.ok()?);
+
+    if year < 1970 || !(1..=12).contains(&month) || !(1..=31).contains(&day)
+        || hour > 23 || minute > 59 || second > 60
+    {
+        return None;
+    }
+
+    // Days since the epoch for a proleptic Gregorian date (Howard Hinnant's algorithm)
+    let (y, m) = if month <= 2 { (year - 1, month + 9) } else { (year, month - 3) };
+    let era = y / 400;
+    let year_of_era = y - era * 400;
+    let day_of_year = (153 * m + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * 146_097 + day_of_era - 719_468;
+
+    Some(days * SECONDS_PER_DAY + hour * 3600 + minute * 60 + second)
+}
@@ -0,0 +1,485 @@
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::collections::VecDeque;
+
+use crate::psl::{ParsedList, RuleKind, Section};
+
+/// Magic bytes at the start of every compiled suffix trie
+pub const MAGIC: &[u8; 4] = b"DVST";
+
+/// Version of the compiled format produced by this build
+pub const FORMAT_VERSION: u16 = 1;
+
+/// Size of the fixed part of the header, before the version and commit strings
+const HEADER_LEN: usize = 28;
+
+/// Size of one encoded node
+const NODE_LEN: usize = 12;
+
+// Node flags
+const FLAG_RULE: u8 = 0b001;
+const FLAG_EXCEPTION: u8 = 0b010;
+const FLAG_PRIVATE: u8 = 0b100;
+
+/// The result of matching a host against the suffix rules
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SuffixMatch {
+    /// Number of labels in the public suffix
+    pub labels: usize,
+    /// Section of the prevailing rule, or `None` if only the implicit `*` rule matched
+    pub section: Option<Section>,
+}
+
+/// A rule recovered from a compiled trie
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct CompiledRule {
+    /// The rule in lowercase punycode form (e.g. `*.ck`, `!www.ck`, `xn--55qx5d.cn`)
+    pub text: String,
+    pub section: Section,
+}
+
+/// A compact, versioned binary form of the Public Suffix List rules.
+///
+/// The rules are stored as a trie of reversed labels (`uk` → `co` → ...) in a
+/// single flat byte buffer:
+///
+/// ```text
+/// header   "DVST", format version (u16), reserved (u16), node count (u32),
+///          label bytes (u32), ICANN rules (u32), private rules (u32),
+///          version length (u16), commit length (u16), version, commit
+/// nodes    node count x { label offset (u32), first child (u32),
+///                         child count (u16), label length (u8), flags (u8) }
+/// labels   all labels, concatenated
+/// ```
+///
+/// All integers are little-endian. Node 0 is the root, and the children of
+/// each node are stored contiguously and sorted by label so they can be
+/// binary searched. The buffer is validated once when it is loaded and is
+/// then queried in place, so a trie can be built over an embedded
+/// (`include_bytes!`) or memory-mapped buffer and lookups never allocate.
+#[derive(Debug, Clone)]
+pub struct SuffixTrie<'a> {
+    data: Cow<'a, [u8]>,
+    node_count: usize,
+    nodes_start: usize,
+    labels_start: usize,
+}
+
+impl SuffixTrie<'static> {
+    /// Compiles the rules of a parsed list into a trie.
+    ///
+    /// Unicode rules are stored in punycode form. If several rules map to the
+    /// same labels, the first one wins.
+    ///
+    /// # Arguments
+    ///
+    /// * `list` - A list parsed with [`crate::psl::parse_list`]
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(SuffixTrie<'static>)` - A trie owning its encoded bytes
+    /// * `Err(String)` - An error message if a label, the version or the
+    ///   commit is too long for the binary form
+    pub fn compile(list: &ParsedList) -> Result<Self, String> {
+        let mut root = BuildNode::default();
+        let mut icann_rules = 0;
+        let mut private_rules = 0;
+
+        for rule in &list.rules {
+            let body = rule.text.trim_start_matches('!');
+            let ascii = match idna::domain_to_ascii(body.trim_start_matches("*.")) {
+                Ok(ascii) => ascii,
+                Err(_) => continue,
+            };
+
+            let mut node = &mut root;
+            for label in ascii.rsplit('.') {
+                node = node.children.entry(label.as_bytes().to_vec()).or_default();
+            }
+            if rule.kind == RuleKind::Wildcard {
+                node = node.children.entry(b"*".to_vec()).or_default();
+            }
+            if node.flags & (FLAG_RULE | FLAG_EXCEPTION) != 0 {
+                continue;
+            }
+
+            node.flags |= match rule.kind {
+                RuleKind::Exception => FLAG_EXCEPTION,
+                _ => FLAG_RULE,
+            };
+            match rule.section {
+                Section::Icann => icann_rules += 1,
+                Section::Private => {
+                    node.flags |= FLAG_PRIVATE;
+                    private_rules += 1;
+                }
+            }
+        }
+
+        let bytes = encode(
+            &root,
+            list.version.as_deref().unwrap_or(""),
+            list.commit.as_deref().unwrap_or(""),
+            icann_rules,
+            private_rules,
+        )?;
+        Ok(SuffixTrie::from_bytes(bytes).expect("a freshly compiled trie is valid"))
+    }
+}
+
+impl<'a> SuffixTrie<'a> {
+    /// Loads a trie from its binary form, validating the whole buffer.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - The encoded trie, either borrowed (`&[u8]`) or owned (`Vec<u8>`)
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(SuffixTrie)` - The loaded trie
+    /// * `Err(String)` - An error message if the buffer is not a valid trie
+    pub fn from_bytes<D: Into<Cow<'a, [u8]>>>(data: D) -> Result<Self, String> {
+        let data = data.into();
+
+        if data.len() < HEADER_LEN || &data[..4] != MAGIC {
+            return Err("Not a compiled suffix trie".to_string());
+        }
+        let version = read_u16(&data, 4);
+        if version != FORMAT_VERSION {
+            return Err(format!(
+                "Unsupported compiled suffix trie version {} (expected {})",
+                version, FORMAT_VERSION
+            ));
+        }
+
+        let node_count = read_u32(&data, 8) as usize;
+        let labels_len = read_u32(&data, 12) as usize;
+        let strings_len = read_u16(&data, 24) as usize + read_u16(&data, 26) as usize;
+        let nodes_start = HEADER_LEN + strings_len;
+        let labels_start = node_count
+            .checked_mul(NODE_LEN)
+            .and_then(|len| len.checked_add(nodes_start))
+            .ok_or_else(truncated)?;
+        if node_count == 0 || labels_start.checked_add(labels_len) != Some(data.len()) {
+            return Err(truncated());
+        }
+
+        let trie = SuffixTrie {
+            data,
+            node_count,
+            nodes_start,
+            labels_start,
+        };
+        trie.validate(labels_len)?;
+
+        Ok(trie)
+    }
+
+    /// Returns the encoded form of the trie
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Returns the `VERSION` header of the list the trie was compiled from
+    pub fn version(&self) -> Option<&str> {
+        let len = read_u16(&self.data, 24) as usize;
+        header_string(&self.data[HEADER_LEN..HEADER_LEN + len])
+    }
+
+    /// Returns the `COMMIT` header of the list the trie was compiled from
+    pub fn commit(&self) -> Option<&str> {
+        let start = HEADER_LEN + read_u16(&self.data, 24) as usize;
+        header_string(&self.data[start..self.nodes_start])
+    }
+
+    /// Returns the number of ICANN and private rules in the trie
+    pub fn rule_counts(&self) -> (usize, usize) {
+        (read_u32(&self.data, 16) as usize, read_u32(&self.data, 20) as usize)
+    }
+
+    /// Finds the public suffix of a host.
+    ///
+    /// The host must be in ASCII (punycode) form; label comparison ignores
+    /// ASCII case. A single trailing dot is ignored. Matching follows the PSL
+    /// algorithm: an exception rule prevails, otherwise the rule with the most
+    /// labels wins, and a host with no matching rule falls back to the
+    /// implicit `*` rule (its last label).
+    ///
+    /// # Arguments
+    ///
+    /// * `host` - The host to look up, e.g. `www.example.co.uk`
+    ///
+    /// # Returns
+    ///
+    /// * `Some(SuffixMatch)` - The number of labels in the public suffix
+    /// * `None` - If the host is empty or contains an empty label
+    pub fn find(&self, host: &str) -> Option<SuffixMatch> {
+        let host = host.strip_suffix('.').unwrap_or(host);
+        if host.is_empty() || host.split('.').any(str::is_empty) {
+            return None;
+        }
+
+        let mut best = SuffixMatch {
+            labels: 1,
+            section: None,
+        };
+        let mut node = 0;
+
+        for (depth, label) in host.rsplit('.').enumerate() {
+            if let Some(wildcard) = self.child(node, b"*") {
+                if self.flags(wildcard) & FLAG_RULE != 0 {
+                    best = SuffixMatch {
+                        labels: depth + 1,
+                        section: Some(self.section(wildcard)),
+                    };
+                }
+            }
+
+            node = match self.child(node, label.as_bytes()) {
+                Some(child) => child,
+                None => break,
+            };
+
+            let flags = self.flags(node);
+            if flags & FLAG_EXCEPTION != 0 {
+                return Some(SuffixMatch {
+                    labels: depth,
+                    section: Some(self.section(node)),
+                });
+            }
+            if flags & FLAG_RULE != 0 {
+                best = SuffixMatch {
+                    labels: depth + 1,
+                    section: Some(self.section(node)),
+                };
+            }
+        }
+
+        Some(best)
+    }
+
+    /// Returns the public suffix of a host as a slice of the input.
+    ///
+    /// A trailing dot on the host is kept on the returned suffix.
+    pub fn suffix<'h>(&self, host: &'h str) -> Option<&'h str> {
+        let found = self.find(host)?;
+        Some(&host[last_labels_start(host, found.labels)?..])
+    }
+
+    /// Returns the registrable domain (eTLD+1) of a host as a slice of the input.
+    ///
+    /// Returns `None` if the host is itself a public suffix.
+    pub fn registrable_domain<'h>(&self, host: &'h str) -> Option<&'h str> {
+        let found = self.find(host)?;
+        Some(&host[last_labels_start(host, found.labels + 1)?..])
+    }
+
+    /// Lists every rule stored in the trie, sorted by text.
+    ///
+    /// This is mainly useful to check that a compiled trie matches the text
+    /// list it was built from.
+    pub fn rules(&self) -> Vec<CompiledRule> {
+        let mut rules = Vec::new();
+        let mut stack: Vec<(usize, String)> = vec![(0, String::new())];
+
+        while let Some((node, name)) = stack.pop() {
+            let flags = self.flags(node);
+            if flags & (FLAG_RULE | FLAG_EXCEPTION) != 0 {
+                let text = if flags & FLAG_EXCEPTION != 0 {
+                    format!("!{}", name)
+                } else {
+                    name.clone()
+                };
+                rules.push(CompiledRule {
+                    text,
+                    section: self.section(node),
+                });
+            }
+
+            let (first, count) = self.children(node);
+            for child in first..first + count {
+                let label = String::from_utf8_lossy(self.label(child));
+                let child_name = if name.is_empty() {
+                    label.into_owned()
+                } else {
+                    format!("{}.{}", label, name)
+                };
+                stack.push((child, child_name));
+            }
+        }
+
+        rules.sort();
+        rules
+    }
+
+    /// Checks every node so that lookups can index the buffer freely
+    fn validate(&self, labels_len: usize) -> Result<(), String> {
+        for node in 0..self.node_count {
+            let offset = self.node_offset(node);
+            let label_start = read_u32(&self.data, offset) as usize;
+            let label_len = self.data[offset + 10] as usize;
+            if label_start + label_len > labels_len {
+                return Err(format!("Label of node {} is out of bounds", node));
+            }
+            if !self.label(node).iter().all(|b| b.is_ascii_graphic() && *b != b'.') {
+                return Err(format!("Label of node {} is not a valid ASCII label", node));
+            }
+
+            // Children must come after their parent, which also rules out cycles
+            let (first, count) = self.children(node);
+            if count > 0 && (first <= node || first + count > self.node_count) {
+                return Err(format!("Children of node {} are out of bounds", node));
+            }
+            for child in first + 1..first + count {
+                if compare_labels(self.label(child - 1), self.label(child)) != Ordering::Less {
+                    return Err(format!("Children of node {} are not sorted", node));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn node_offset(&self, node: usize) -> usize {
+        self.nodes_start + node * NODE_LEN
+    }
+
+    fn label(&self, node: usize) -> &[u8] {
+        let offset = self.node_offset(node);
+        let start = self.labels_start + read_u32(&self.data, offset) as usize;
+        let len = self.data[offset + 10] as usize;
+        &self.data[start..start + len]
+    }
+
+    fn children(&self, node: usize) -> (usize, usize) {
+        let offset = self.node_offset(node);
+        (
+            read_u32(&self.data, offset + 4) as usize,
+            read_u16(&self.data, offset + 8) as usize,
+        )
+    }
+
+    fn flags(&self, node: usize) -> u8 {
+        self.data[self.node_offset(node) + 11]
+    }
+
+    fn section(&self, node: usize) -> Section {
+        if self.flags(node) & FLAG_PRIVATE != 0 {
+            Section::Private
+        } else {
+            Section::Icann
+        }
+    }
+
+    /// Binary searches the children of a node for a label
+    fn child(&self, node: usize, label: &[u8]) -> Option<usize> {
+        let (mut low, mut high) = self.children(node);
+        high += low;
+        while low < high {
+            let mid = low + (high - low) / 2;
+            match compare_labels(self.label(mid), label) {
+                Ordering::Less => low = mid + 1,
+                Ordering::Greater => high = mid,
+                Ordering::Equal => return Some(mid),
+            }
+        }
+        None
+    }
+}
+
+/// A node of the trie while it is being built
+#[derive(Default)]
+struct BuildNode {
+    children: BTreeMap<Vec<u8>, BuildNode>,
+    flags: u8,
+}
+
+/// Lays the trie out breadth-first so the children of each node are contiguous
+fn encode(root: &BuildNode, version: &str, commit: &str, icann: usize, private: usize) -> Result<Vec<u8>, String> {
+    let mut nodes = Vec::new();
+    let mut labels = Vec::new();
+    let mut queue = VecDeque::from([(root, &[][..])]);
+    let mut next_index = 1;
+
+    while let Some((node, label)) = queue.pop_front() {
+        let first_child = if node.children.is_empty() { 0 } else { next_index };
+        next_index += node.children.len();
+
+        nodes.extend_from_slice(&field::<u32>(labels.len(), "the label data")?.to_le_bytes());
+        nodes.extend_from_slice(&field::<u32>(first_child, "the node count")?.to_le_bytes());
+        nodes.extend_from_slice(&field::<u16>(node.children.len(), "the number of children of a label")?.to_le_bytes());
+        nodes.push(field::<u8>(label.len(), &format!("label '{}'", String::from_utf8_lossy(label)))?);
+        nodes.push(node.flags);
+        labels.extend_from_slice(label);
+
+        for (child_label, child) in &node.children {
+            queue.push_back((child, child_label.as_slice()));
+        }
+    }
+
+    let mut data = Vec::with_capacity(HEADER_LEN + nodes.len() + labels.len());
+    data.extend_from_slice(MAGIC);
+    data.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+    data.extend_from_slice(&0u16.to_le_bytes());
+    data.extend_from_slice(&field::<u32>(nodes.len() / NODE_LEN, "the node count")?.to_le_bytes());
+    data.extend_from_slice(&field::<u32>(labels.len(), "the label data")?.to_le_bytes());
+    data.extend_from_slice(&field::<u32>(icann, "the ICANN rule count")?.to_le_bytes());
+    data.extend_from_slice(&field::<u32>(private, "the private rule count")?.to_le_bytes());
+    data.extend_from_slice(&field::<u16>(version.len(), "the version")?.to_le_bytes());
+    data.extend_from_slice(&field::<u16>(commit.len(), "the commit")?.to_le_bytes());
+    data.extend_from_slice(version.as_bytes());
+    data.extend_from_slice(commit.as_bytes());
+    data.extend_from_slice(&nodes);
+    data.extend_from_slice(&labels);
+    Ok(data)
+}
+
+/// Converts a length or count to the width of its field in the binary form,
+/// naming `what` was too long otherwise
+fn field<T: TryFrom<usize>>(value: usize, what: &str) -> Result<T, String> {
+    T::try_from(value).map_err(|_| {
+        format!(
+            "Failed to compile Public Suffix List: {} is too long ({} does not fit in {} bytes)",
+            what,
+            value,
+            std::mem::size_of::<T>()
+        )
+    })
+}
+
+/// Orders labels by their lowercase bytes, without allocating
+fn compare_labels(a: &[u8], b: &[u8]) -> Ordering {
+    a.iter()
+        .map(u8::to_ascii_lowercase)
+        .cmp(b.iter().map(u8::to_ascii_lowercase))
+}
+
+/// Returns the byte offset where the last `count` labels of a host start
+pub(crate) fn last_labels_start(host: &str, count: usize) -> Option<usize> {
+    let trimmed = host.strip_suffix('.').unwrap_or(host);
+    let mut start = trimmed.len();
+    for _ in 0..count {
+        if start == 0 {
+            return None;
+        }
+        start = trimmed[..start - 1].rfind('.').map_or(0, |dot| dot + 1);
+    }
+    Some(start)
+}
+
+fn header_string(bytes: &[u8]) -> Option<&str> {
+    std::str::from_utf8(bytes).ok().filter(|s| !s.is_empty())
+}
+
+fn read_u16(data: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([data[offset], data[offset + 1]])
+}
+
+fn read_u32(data: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes([data[offset], data[offset + 1], data[offset + 2], data[offset + 3]])
+}
+
+fn truncated() -> String {
+    "Compiled suffix trie is truncated or has an invalid size".to_string()
+}
@@ -0,0 +1,308 @@
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::domain::{Domain, RootDomain};
+use crate::idna_options;
+use crate::suffix_store::SuffixStore;
+
+/// Rows of a QWERTY keyboard, used for adjacent-key replacements
+const KEYBOARD_ROWS: [&str; 4] = ["1234567890-", "qwertyuiop", "asdfghjkl", "zxcvbnm"];
+
+/// Vowels swapped with each other by [`Technique::VowelSwap`]
+const VOWELS: &str = "aeiou";
+
+/// Look-alike replacements for a character or character sequence
+const HOMOGLYPHS: &[(&str, &[&str])] = &[
+    ("a", &["а", "à", "á", "â", "ä", "ɑ"]),
+    ("b", &["d", "lb", "ь"]),
+    ("c", &["с", "ϲ", "e"]),
+    ("d", &["b", "cl", "ԁ"]),
+    ("e", &["е", "é", "ê", "ë", "c"]),
+    ("g", &["q", "ɡ"]),
+    ("h", &["lh", "һ"]),
+    ("i", &["1", "l", "і", "í", "ï"]),
+    ("j", &["ј", "i"]),
+    ("k", &["lk", "ik", "lc", "κ"]),
+    ("l", &["1", "i", "ӏ"]),
+    ("m", &["n", "nn", "rn", "rr"]),
+    ("n", &["m", "r", "ո"]),
+    ("o", &["0", "о", "ο", "ö", "ó"]),
+    ("p", &["р", "ρ"]),
+    ("q", &["g", "ԛ"]),
+    ("r", &["ɾ"]),
+    ("s", &["ѕ", "5"]),
+    ("t", &["τ"]),
+    ("u", &["υ", "ս", "ü", "v"]),
+    ("v", &["ѵ", "u"]),
+    ("w", &["vv", "ԝ"]),
+    ("x", &["х"]),
+    ("y", &["у", "ý"]),
+    ("z", &["2", "ż"]),
+    ("0", &["o"]),
+    ("1", &["l", "i"]),
+    ("rn", &["m"]),
+    ("cl", &["d"]),
+    ("vv", &["w"]),
+];
+
+/// A way of deriving a look-alike domain from a root domain
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Technique {
+    /// A character left out: `exmple.com`
+    Omission,
+    /// A character typed twice: `exammple.com`
+    Repetition,
+    /// Two adjacent characters swapped: `exmaple.com`
+    Transposition,
+    /// A character replaced by a neighbouring QWERTY key: `exsmple.com`
+    AdjacentKey,
+    /// One bit flipped in a character: `uxample.com`
+    Bitsquatting,
+    /// A character replaced by a look-alike: `exаmple.com` (Cyrillic а), `rnicrosoft.com`
+    Homoglyph,
+    /// A hyphen inserted: `exam-ple.com`
+    Hyphenation,
+    /// A vowel replaced by another vowel: `exomple.com`
+    VowelSwap,
+    /// The same name under another public suffix: `example.net`
+    AlternateSuffix,
+}
+
+impl Technique {
+    /// Every technique, in the order candidates are generated
+    pub const ALL: [Technique; 9] = [
+        Technique::Omission,
+        Technique::Repetition,
+        Technique::Transposition,
+        Technique::AdjacentKey,
+        Technique::Bitsquatting,
+        Technique::Homoglyph,
+        Technique::Hyphenation,
+        Technique::VowelSwap,
+        Technique::AlternateSuffix,
+    ];
+
+    /// Returns the kebab-case name of the technique, e.g. `adjacent-key`
+    pub fn name(self) -> &'static str {
+        match self {
+            Technique::Omission => "omission",
+            Technique::Repetition => "repetition",
+            Technique::Transposition => "transposition",
+            Technique::AdjacentKey => "adjacent-key",
+            Technique::Bitsquatting => "bitsquatting",
+            Technique::Homoglyph => "homoglyph",
+            Technique::Hyphenation => "hyphenation",
+            Technique::VowelSwap => "vowel-swap",
+            Technique::AlternateSuffix => "alternate-suffix",
+        }
+    }
+}
+
+impl fmt::Display for Technique {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Technique {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Technique::ALL
+            .into_iter()
+            .find(|technique| technique.name() == s)
+            .ok_or_else(|| format!("Unknown typosquatting technique '{}'", s))
+    }
+}
+
+/// A look-alike candidate for a root domain
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Typosquat {
+    /// The candidate for display, with internationalized labels in Unicode
+    pub domain: String,
+    /// The candidate in ASCII (punycode) form, as it would be registered
+    pub ascii: String,
+    /// How the candidate was derived
+    pub technique: Technique,
+}
+
+/// Generates typosquatting candidates for a root domain.
+///
+/// The techniques other than [`Technique::AlternateSuffix`] change the
+/// leftmost label of the root domain (`example` in `example.co.uk`) and keep
+/// its public suffix. Alternate suffixes are drawn from the ICANN section of
+/// the loaded list: every top-level domain, plus every suffix under the same
+/// top-level domain as the original (`org.uk`, `me.uk`, … for `co.uk`).
+///
+/// Candidates that are not valid domain names are dropped, as is the root
+/// domain itself. A candidate produced by several techniques is listed once,
+/// under the first technique in `techniques` that produced it.
+///
+/// # Arguments
+///
+/// * `store` - The Public Suffix List the root domain was extracted with
+/// * `root` - The root domain to generate candidates for
+/// * `techniques` - The techniques to apply, e.g. [`Technique::ALL`]
+///
+/// # Returns
+///
+/// The candidates, grouped by technique in the order given
+pub fn generate(store: &SuffixStore, root: &RootDomain, techniques: &[Technique]) -> Vec<Typosquat> {
+    let (name, suffix) = root.as_str().split_once('.').unwrap_or((root.as_str(), ""));
+    let name = idna_options::to_unicode(name);
+
+    let mut seen: HashSet<String> = HashSet::from([root.as_str().to_string()]);
+    let mut candidates = Vec::new();
+
+    for &technique in techniques {
+        let variants = match technique {
+            Technique::AlternateSuffix => alternate_suffixes(store, suffix)
+                .into_iter()
+                .map(|alternate| format!("{}.{}", name, alternate))
+                .collect(),
+            technique => name_variants(&name, technique)
+                .into_iter()
+                .map(|variant| format!("{}.{}", variant, suffix))
+                .collect::<Vec<_>>(),
+        };
+
+        for variant in variants {
+            let Ok(domain) = Domain::parse(&variant) else {
+                continue;
+            };
+            if seen.insert(domain.as_str().to_string()) {
+                candidates.push(Typosquat {
+                    domain: domain.to_unicode(),
+                    ascii: domain.into(),
+                    technique,
+                });
+            }
+        }
+    }
+
+    candidates
+}
+
+/// Applies one technique to the registrable label, returning every variant
+fn name_variants(name: &str, technique: Technique) -> Vec<String> {
+    let chars: Vec<char> = name.chars().collect();
+    let rebuild = |before: &[char], middle: &str, after: &[char]| -> String {
+        before.iter().copied().chain(middle.chars()).chain(after.iter().copied()).collect()
+    };
+    let mut variants = Vec::new();
+
+    match technique {
+        Technique::Omission => {
+            for i in 0..chars.len() {
+                variants.push(rebuild(&chars[..i], "", &chars[i + 1..]));
+            }
+        }
+        Technique::Repetition => {
+            for i in 0..chars.len() {
+                variants.push(rebuild(&chars[..=i], &chars[i].to_string(), &chars[i + 1..]));
+            }
+        }
+        Technique::Transposition => {
+            for i in 1..chars.len() {
+                if chars[i - 1] != chars[i] {
+                    let mut swapped = chars.clone();
+                    swapped.swap(i - 1, i);
+                    variants.push(swapped.into_iter().collect());
+                }
+            }
+        }
+        Technique::AdjacentKey => {
+            for (i, &c) in chars.iter().enumerate() {
+                for neighbour in adjacent_keys(c) {
+                    variants.push(rebuild(&chars[..i], &neighbour.to_string(), &chars[i + 1..]));
+                }
+            }
+        }
+        Technique::Bitsquatting => {
+            for (i, &c) in chars.iter().enumerate() {
+                if !c.is_ascii() {
+                    continue;
+                }
+                for bit in 0..8 {
+                    let flipped = char::from(c as u8 ^ (1 << bit));
+                    if flipped.is_ascii_lowercase() || flipped.is_ascii_digit() || flipped == '-' {
+                        variants.push(rebuild(&chars[..i], &flipped.to_string(), &chars[i + 1..]));
+                    }
+                }
+            }
+        }
+        Technique::Homoglyph => {
+            for (i, _) in name.char_indices() {
+                let (before, rest) = name.split_at(i);
+                for (original, lookalikes) in HOMOGLYPHS {
+                    if let Some(after) = rest.strip_prefix(original) {
+                        for lookalike in lookalikes.iter() {
+                            variants.push(format!("{}{}{}", before, lookalike, after));
+                        }
+                    }
+                }
+            }
+        }
+        Technique::Hyphenation => {
+            for i in 1..chars.len() {
+                if chars[i - 1] != '-' && chars[i] != '-' {
+                    variants.push(rebuild(&chars[..i], "-", &chars[i..]));
+                }
+            }
+        }
+        Technique::VowelSwap => {
+            for (i, &c) in chars.iter().enumerate() {
+                if VOWELS.contains(c) {
+                    for vowel in VOWELS.chars().filter(|&vowel| vowel != c) {
+                        variants.push(rebuild(&chars[..i], &vowel.to_string(), &chars[i + 1..]));
+                    }
+                }
+            }
+        }
+        Technique::AlternateSuffix => {}
+    }
+
+    variants
+}
+
+/// Returns the keys next to `key` on a QWERTY keyboard
+fn adjacent_keys(key: char) -> Vec<char> {
+    let rows: Vec<Vec<char>> = KEYBOARD_ROWS.iter().map(|row| row.chars().collect()).collect();
+    let Some((row, col)) = rows
+        .iter()
+        .enumerate()
+        .find_map(|(row, keys)| keys.iter().position(|&k| k == key).map(|col| (row, col)))
+    else {
+        return Vec::new();
+    };
+
+    // Each row is shifted right by about half a key relative to the row above
+    let mut positions = vec![(row, col.wrapping_sub(1)), (row, col + 1)];
+    if row > 0 {
+        positions.extend([(row - 1, col), (row - 1, col + 1)]);
+    }
+    if row + 1 < rows.len() {
+        positions.extend([(row + 1, col.wrapping_sub(1)), (row + 1, col)]);
+    }
+
+    positions
+        .into_iter()
+        .filter_map(|(row, col)| rows[row].get(col).copied())
+        .collect()
+}
+
+/// Returns the public suffixes a name could be registered under instead of `suffix`
+fn alternate_suffixes(store: &SuffixStore, suffix: &str) -> Vec<String> {
+    let tld = suffix.rsplit('.').next().unwrap_or(suffix);
+
+    store
+        .icann_suffixes()
+        .iter()
+        .filter(|text| *text != suffix)
+        .filter(|text| !text.contains('.') || text.rsplit('.').next() == Some(tld))
+        .cloned()
+        .collect()
+}
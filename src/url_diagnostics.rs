@@ -0,0 +1,360 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::suffix_store::SuffixStore;
+
+/// Schemes that misspelled schemes are compared against
+const WEB_SCHEMES: &[&str] = &["http", "https"];
+
+/// Top-level domains preferred when an unknown one is as close to several others
+const COMMON_TLDS: &[&str] = &["com", "net", "org", "info", "io", "co", "uk", "de"];
+
+/// A range of bytes in the input, end exclusive
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+/// The kind of problem found in a URL
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DiagnosticKind {
+    /// Whitespace around the URL or inside its host
+    Whitespace,
+    /// A scheme one edit away from `http` or `https`, such as `htp`
+    MisspelledScheme,
+    /// The scheme is not followed by exactly `://`, as in `http:/` or `http//`
+    MalformedSchemeSeparator,
+    /// The URL has a scheme but no host
+    MissingHost,
+    /// A leading or doubled dot in the host
+    EmptyLabel,
+    /// A character that cannot appear in a host name
+    InvalidCharacter,
+    /// A port that is not a number from 0 to 65535
+    InvalidPort,
+    /// A top-level domain that is not in the Public Suffix List
+    UnknownTld,
+}
+
+/// A problem in a URL, located in the input
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Diagnostic {
+    pub kind: DiagnosticKind,
+    /// Where the problem is, in bytes of the input
+    pub span: Span,
+    /// What is wrong, for humans
+    pub message: String,
+    /// The text to replace the span with to fix the problem, empty to remove
+    /// it, if a fix can be guessed
+    pub suggestion: Option<String>,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "bytes {}..{}: {}", self.span.start, self.span.end, self.message)?;
+        match self.suggestion.as_deref() {
+            Some("") => write!(f, " (remove it)"),
+            Some(suggestion) => write!(f, " (did you mean '{}'?)", suggestion),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Finds the problems in a URL that can be told from its text alone.
+///
+/// The checks are lenient where the URL parser is: a URL without a scheme
+/// is fine, as is a trailing dot on the host. The host and port are only
+/// checked after `://` or when there is no scheme, so opaque URIs such as
+/// `mailto:` or `data:` are left alone. Whatever the parser would
+/// accept or reject, each problem found is located by its byte span in
+/// `url_str`, so it can be pointed out to the user.
+///
+/// # Arguments
+///
+/// * `url_str` - The URL as given by the user, e.g. `htp:/exa mple..com`
+///
+/// # Returns
+///
+/// The problems found, ordered by position
+pub fn diagnose(url_str: &str) -> Vec<Diagnostic> {
+    let (mut diagnostics, _) = analyze(url_str);
+    diagnostics.sort_by_key(|diagnostic| diagnostic.span.start);
+    diagnostics
+}
+
+/// Finds the problems in a URL, like [`diagnose`], and also checks its
+/// top-level domain against the Public Suffix List.
+///
+/// An unknown top-level domain one edit away from a real one, such as `cmo`
+/// or `comm`, comes with the real one as suggestion.
+///
+/// # Arguments
+///
+/// * `store` - The Public Suffix List holding the known top-level domains
+/// * `url_str` - The URL as given by the user
+///
+/// # Returns
+///
+/// The problems found, ordered by position
+pub fn diagnose_with(store: &SuffixStore, url_str: &str) -> Vec<Diagnostic> {
+    let (mut diagnostics, host) = analyze(url_str);
+    diagnostics.extend(host.and_then(|host| check_tld(store, url_str, host)));
+    diagnostics.sort_by_key(|diagnostic| diagnostic.span.start);
+    diagnostics
+}
+
+/// Applies the suggestions of a set of diagnostics to the input.
+///
+/// Diagnostics without a suggestion, or overlapping an earlier one, are
+/// skipped.
+///
+/// # Arguments
+///
+/// * `url_str` - The URL the diagnostics were made for
+/// * `diagnostics` - The diagnostics, as returned by [`diagnose`]
+///
+/// # Returns
+///
+/// * `Some(String)` - The corrected URL, e.g. `http://example.com`
+/// * `None` - No diagnostic has a suggestion
+pub fn apply_suggestions(url_str: &str, diagnostics: &[Diagnostic]) -> Option<String> {
+    let mut fixes: Vec<(Span, &str)> = diagnostics
+        .iter()
+        .filter_map(|diagnostic| Some((diagnostic.span, diagnostic.suggestion.as_deref()?)))
+        .collect();
+    if fixes.is_empty() {
+        return None;
+    }
+    fixes.sort_by_key(|(span, _)| span.start);
+
+    let mut corrected = String::with_capacity(url_str.len());
+    let mut copied = 0;
+    for (span, replacement) in fixes {
+        if span.start < copied || span.end > url_str.len() {
+            continue;
+        }
+        corrected.push_str(&url_str[copied..span.start]);
+        corrected.push_str(replacement);
+        copied = span.end;
+    }
+    corrected.push_str(&url_str[copied..]);
+    Some(corrected)
+}
+
+/// Runs the text checks, returning the diagnostics and the span of the host
+fn analyze(url_str: &str) -> (Vec<Diagnostic>, Option<Span>) {
+    let mut diagnostics = Vec::new();
+    let mut report = |kind, start, end, message: String, suggestion: Option<&str>| {
+        diagnostics.push(Diagnostic {
+            kind,
+            span: Span { start, end },
+            message,
+            suggestion: suggestion.map(str::to_string),
+        })
+    };
+
+    // Whitespace around the URL
+    let start = url_str.len() - url_str.trim_start().len();
+    let end = url_str.trim_end().len().max(start);
+    if start > 0 {
+        report(DiagnosticKind::Whitespace, 0, start, "leading whitespace".to_string(), Some(""));
+    }
+    if end < url_str.len() {
+        report(DiagnosticKind::Whitespace, end, url_str.len(), "trailing whitespace".to_string(), Some(""));
+    }
+    if start == end {
+        return (diagnostics, None);
+    }
+
+    // The scheme and the `://` after it
+    let text = &url_str[start..end];
+    let scheme_len = text
+        .find(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.')))
+        .unwrap_or(text.len());
+    let scheme = &text[..scheme_len];
+    let rest = &text[scheme_len..];
+    let colons = usize::from(rest.starts_with(':'));
+    let slashes = rest[colons..].chars().take_while(|&c| c == '/' || c == '\\').count();
+    let separator_len = colons + slashes;
+
+    let web_scheme = web_scheme(scheme);
+    let mut authority_start = start;
+    if separator_len > 0 && (web_scheme.is_some() || (colons == 1 && slashes == 2)) {
+        if let Some(web_scheme) = web_scheme.filter(|web_scheme| !scheme.eq_ignore_ascii_case(web_scheme)) {
+            report(
+                DiagnosticKind::MisspelledScheme,
+                start,
+                start + scheme_len,
+                format!("unknown scheme '{}'", scheme),
+                Some(web_scheme),
+            );
+        }
+
+        let separator_start = start + scheme_len;
+        if web_scheme.is_some() && (colons, slashes) != (1, 2) {
+            let message = if colons == 0 {
+                format!("missing ':' after the scheme '{}'", scheme)
+            } else if slashes < 2 {
+                format!("missing '/' after '{}:'", scheme)
+            } else {
+                format!("too many slashes after '{}:'", scheme)
+            };
+            report(
+                DiagnosticKind::MalformedSchemeSeparator,
+                separator_start,
+                separator_start + separator_len,
+                message,
+                Some("://"),
+            );
+        }
+        authority_start = separator_start + separator_len;
+    } else if colons == 1 && scheme.starts_with(|c: char| c.is_ascii_alphabetic()) && !scheme.contains('.') {
+        // An opaque URI such as `mailto:` or `data:` has no host to check;
+        // a dotted name before the colon is a host with a port
+        return (diagnostics, None);
+    }
+
+    // The authority: user info, host and port
+    let authority_len = url_str[authority_start..end]
+        .find(['/', '\\', '?', '#'])
+        .unwrap_or(end - authority_start);
+    let authority = &url_str[authority_start..authority_start + authority_len];
+    let host_start = authority_start + authority.rfind('@').map_or(0, |at| at + 1);
+    let mut host_end = authority_start + authority_len;
+
+    if url_str[host_start..host_end].starts_with('[') {
+        // IPv6 addresses are left to the URL parser
+        return (diagnostics, None);
+    }
+    if let Some(colon) = url_str[host_start..host_end].rfind(':') {
+        let port = &url_str[host_start + colon + 1..host_end];
+        if !port.is_empty() && port.parse::<u16>().is_err() {
+            report(
+                DiagnosticKind::InvalidPort,
+                host_start + colon,
+                host_end,
+                format!("port '{}' is not a number from 0 to 65535", port),
+                Some(""),
+            );
+        }
+        host_end = host_start + colon;
+    }
+
+    if host_start == host_end {
+        if authority_start > start {
+            report(DiagnosticKind::MissingHost, host_start, host_end, "the URL has no host".to_string(), None);
+        }
+        return (diagnostics, None);
+    }
+
+    // The characters of the host
+    let host = &url_str[host_start..host_end];
+    let mut chars = host.char_indices().peekable();
+    while let Some((offset, c)) = chars.next() {
+        let run_start = host_start + offset;
+        if c.is_whitespace() || c == '.' {
+            // Runs of whitespace or dots are reported once
+            let same = |next: char| if c == '.' { next == '.' } else { next.is_whitespace() };
+            let mut run_end = run_start + c.len_utf8();
+            while let Some((next_offset, next)) = chars.next_if(|&(_, next)| same(next)) {
+                run_end = host_start + next_offset + next.len_utf8();
+            }
+
+            if c != '.' {
+                report(DiagnosticKind::Whitespace, run_start, run_end, "whitespace in the host".to_string(), Some(""));
+            } else if run_start == host_start {
+                report(DiagnosticKind::EmptyLabel, run_start, run_end, "the host starts with a dot".to_string(), Some(""));
+            } else if run_end - run_start > 1 {
+                report(DiagnosticKind::EmptyLabel, run_start + 1, run_end, "doubled dot in the host".to_string(), Some(""));
+            }
+        } else if c.is_ascii() && !(c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '%')) {
+            report(
+                DiagnosticKind::InvalidCharacter,
+                run_start,
+                run_start + 1,
+                format!("'{}' is not allowed in a host name", c),
+                None,
+            );
+        }
+    }
+
+    (diagnostics, Some(Span { start: host_start, end: host_end }))
+}
+
+/// Returns the web scheme a scheme is, or is one edit away from
+fn web_scheme(scheme: &str) -> Option<&'static str> {
+    let scheme = scheme.to_ascii_lowercase();
+    WEB_SCHEMES
+        .iter()
+        .find(|&&web_scheme| scheme == web_scheme)
+        .or_else(|| WEB_SCHEMES.iter().find(|&&web_scheme| edit_distance(&scheme, web_scheme) == 1))
+        .copied()
+}
+
+/// Reports the top-level domain of a host if the Public Suffix List does not know it
+fn check_tld(store: &SuffixStore, url_str: &str, host: Span) -> Option<Diagnostic> {
+    let host_text = &url_str[host.start..host.end];
+    let trimmed = host_text.strip_suffix('.').unwrap_or(host_text);
+    let (_, tld) = trimmed.rsplit_once('.')?;
+    if tld.is_empty() || !tld.chars().all(|c| c.is_ascii_alphabetic()) {
+        return None;
+    }
+
+    let lowercase = tld.to_ascii_lowercase();
+    if store.trie().find(&lowercase)?.section.is_some() {
+        return None;
+    }
+
+    let start = host.start + trimmed.len() - tld.len();
+    Some(Diagnostic {
+        kind: DiagnosticKind::UnknownTld,
+        span: Span {
+            start,
+            end: start + tld.len(),
+        },
+        message: format!("'{}' is not a top-level domain", tld),
+        suggestion: closest_tld(store, &lowercase),
+    })
+}
+
+/// Finds the ICANN top-level domain one edit away from `tld`, preferring
+/// substitutions and transpositions, then common top-level domains
+fn closest_tld(store: &SuffixStore, tld: &str) -> Option<String> {
+    store
+        .icann_suffixes()
+        .iter()
+        .filter(|candidate| !candidate.contains('.') && edit_distance(tld, candidate) == 1)
+        .min_by_key(|candidate| {
+            let common = COMMON_TLDS.iter().position(|common| common == *candidate).unwrap_or(COMMON_TLDS.len());
+            (candidate.len() != tld.len(), common, *candidate)
+        })
+        .cloned()
+}
+
+/// Optimal string alignment distance: insertions, deletions, substitutions
+/// and transpositions of adjacent characters each count as one edit
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut rows = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in rows[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut distance = (rows[i - 1][j] + 1).min(rows[i][j - 1] + 1).min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = distance;
+        }
+    }
+    rows[a.len()][b.len()]
+}
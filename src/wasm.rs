@@ -0,0 +1,80 @@
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+use crate::domain_parser;
+
+/// Result of `parse_url`, as returned to JavaScript
+#[derive(Debug, Serialize)]
+struct ParseUrlResult {
+    original_url: String,
+    host: Option<String>,
+    error: Option<String>,
+}
+
+/// Result of `extract_root_domain`, as returned to JavaScript
+#[derive(Debug, Serialize)]
+struct RootDomainResult {
+    domain: String,
+    root_domain: Option<String>,
+    error: Option<String>,
+}
+
+/// Result of `extract_root_domain_from_url`, shaped like the `/parse` response
+#[derive(Debug, Serialize)]
+struct UrlRootDomainResult {
+    original_url: String,
+    root_domain: Option<String>,
+    error: Option<String>,
+}
+
+/// Parses a URL and returns `{ original_url, host, error }`.
+///
+/// See [`domain_parser::parse_url`].
+#[wasm_bindgen]
+pub fn parse_url(url: &str) -> Result<JsValue, JsValue> {
+    let (host, error) = split_result(domain_parser::parse_url(url));
+    to_js(&ParseUrlResult {
+        original_url: url.to_string(),
+        host,
+        error,
+    })
+}
+
+/// Extracts the root domain of a domain and returns `{ domain, root_domain, error }`.
+///
+/// See [`domain_parser::extract_root_domain`]. The embedded Public Suffix List is used.
+#[wasm_bindgen]
+pub fn extract_root_domain(domain: &str) -> Result<JsValue, JsValue> {
+    let (root_domain, error) = split_result(domain_parser::extract_root_domain(domain));
+    to_js(&RootDomainResult {
+        domain: domain.to_string(),
+        root_domain,
+        error,
+    })
+}
+
+/// Extracts the root domain of a URL and returns `{ original_url, root_domain, error }`.
+///
+/// See [`domain_parser::extract_root_domain_from_url`]. The embedded Public Suffix List is used.
+#[wasm_bindgen]
+pub fn extract_root_domain_from_url(url: &str) -> Result<JsValue, JsValue> {
+    let (root_domain, error) = split_result(domain_parser::extract_root_domain_from_url(url));
+    to_js(&UrlRootDomainResult {
+        original_url: url.to_string(),
+        root_domain,
+        error,
+    })
+}
+
+fn split_result(result: Result<String, String>) -> (Option<String>, Option<String>) {
+    match result {
+        Ok(value) => (Some(value), None),
+        Err(e) => (None, Some(e)),
+    }
+}
+
+fn to_js<T: Serialize>(value: &T) -> Result<JsValue, JsValue> {
+    // Serialize missing values as `null` rather than `undefined`, like the JSON API
+    let serializer = serde_wasm_bindgen::Serializer::json_compatible();
+    value.serialize(&serializer).map_err(JsValue::from)
+}
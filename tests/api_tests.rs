@@ -3,11 +3,17 @@ use axum::{
     http::{Request, StatusCode},
     response::Response,
 };
-use domain_validator::api::{create_router, ParseResponse};
-use serde_json;
+use domain_validator::api::{
+    create_router, create_router_with_state, create_router_with_store, AppState, LookalikeResponse, ParseResponse,
+};
+use domain_validator::homograph::Risk;
+use domain_validator::idna_options::IdnaOptions;
+use domain_validator::lookalike::ProtectedDomains;
+use domain_validator::redirect::Unwrapper;
+use domain_validator::suffix_store::{PslMetadata, SuffixStore};
+use std::sync::Arc;
 use tower::ServiceExt;
 use http_body_util::BodyExt as _;
-use reqwest;
 
 #[tokio::test]
 async fn test_root_endpoint() {
@@ -244,4 +250,545 @@ async fn test_parse_missing_url() {
     } else {
         assert_ne!(status, StatusCode::OK);
     }
-} 
\ No newline at end of file
+} 
+#[tokio::test]
+async fn test_psl_info_endpoint() {
+    let app = create_router();
+
+    let response = app
+        .oneshot(Request::builder().uri("/psl/info").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body_bytes = response.into_body().collect().await.unwrap().to_bytes();
+    let info: PslMetadata = serde_json::from_slice(&body_bytes).unwrap();
+
+    assert_eq!(info.version.as_deref(), Some("2025-02-27_07-53-30_UTC"));
+    assert_eq!(info.commit.as_deref(), Some("23f7ef2b96a01338ac761a395aa5d37572850567"));
+    assert!(info.icann_rules > 0);
+    assert!(info.private_rules > 0);
+    assert_eq!(info.source, "public_suffix_list.dat");
+}
+
+#[tokio::test]
+async fn test_parse_endpoint_debug_block() {
+    let app = create_router();
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/parse?url=https://www.example.com&debug=true")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    let body_bytes = response.into_body().collect().await.unwrap().to_bytes();
+    let body: ParseResponse = serde_json::from_slice(&body_bytes).unwrap();
+
+    assert_eq!(body.root_domain.as_deref(), Some("example.com"));
+    let debug = body.debug.expect("debug block requested");
+    assert_eq!(debug.psl.version.as_deref(), Some("2025-02-27_07-53-30_UTC"));
+}
+
+#[tokio::test]
+async fn test_parse_endpoint_omits_debug_block_by_default() {
+    let app = create_router();
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/parse?url=https://www.example.com")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    let body_bytes = response.into_body().collect().await.unwrap().to_bytes();
+    let body: serde_json::Value = serde_json::from_slice(&body_bytes).unwrap();
+
+    assert!(body.get("debug").is_none());
+}
+
+#[tokio::test]
+async fn test_typosquats_endpoint() {
+    let app = create_router();
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/typosquats?domain=www.example.com&techniques=omission,alternate-suffix")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body_bytes = response.into_body().collect().await.unwrap().to_bytes();
+    let body: serde_json::Value = serde_json::from_slice(&body_bytes).unwrap();
+
+    assert_eq!(body["domain"], "www.example.com");
+    assert_eq!(body["root_domain"], "example.com");
+    assert!(body["error"].is_null());
+
+    let candidates = body["candidates"].as_array().unwrap();
+    assert!(candidates.iter().any(|c| c["domain"] == "exmple.com" && c["technique"] == "omission"));
+    assert!(candidates.iter().any(|c| c["domain"] == "example.net" && c["technique"] == "alternate-suffix"));
+    assert!(candidates.iter().all(|c| c["technique"] == "omission" || c["technique"] == "alternate-suffix"));
+}
+
+#[tokio::test]
+async fn test_typosquats_endpoint_debug_block() {
+    for (uri, requested) in [
+        ("/typosquats?domain=example.com&techniques=omission&debug=true", true),
+        ("/typosquats?domain=example.com&techniques=omission", false),
+    ] {
+        let response = create_router()
+            .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+
+        let body_bytes = response.into_body().collect().await.unwrap().to_bytes();
+        let body: serde_json::Value = serde_json::from_slice(&body_bytes).unwrap();
+
+        if requested {
+            assert_eq!(body["debug"]["psl"]["version"], "2025-02-27_07-53-30_UTC");
+        } else {
+            assert!(body.get("debug").is_none());
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_typosquats_endpoint_errors() {
+    for uri in ["/typosquats?domain=not%20a%20domain", "/typosquats?domain=example.com&techniques=bogus"] {
+        let response = create_router()
+            .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body_bytes = response.into_body().collect().await.unwrap().to_bytes();
+        let body: serde_json::Value = serde_json::from_slice(&body_bytes).unwrap();
+
+        assert!(body["error"].is_string(), "{}", uri);
+        assert!(body["candidates"].as_array().unwrap().is_empty());
+    }
+}
+
+#[tokio::test]
+async fn test_parse_endpoint_homograph_verdict() {
+    let app = create_router();
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/parse?url=https://xn--pple-43d.com/login")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    let body_bytes = response.into_body().collect().await.unwrap().to_bytes();
+    let body: ParseResponse = serde_json::from_slice(&body_bytes).unwrap();
+
+    let homograph = body.homograph.expect("homograph block for IDN host");
+    assert_eq!(homograph.risk, Risk::High);
+    assert_eq!(homograph.labels[0].scripts, vec!["Cyrillic", "Latin"]);
+}
+
+#[tokio::test]
+async fn test_parse_endpoint_omits_homograph_for_ascii_hosts() {
+    let app = create_router();
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/parse?url=https://www.example.com")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    let body_bytes = response.into_body().collect().await.unwrap().to_bytes();
+    let body: serde_json::Value = serde_json::from_slice(&body_bytes).unwrap();
+
+    assert!(body.get("homograph").is_none());
+}
+
+#[tokio::test]
+async fn test_parse_endpoint_repertoire() {
+    let app = create_router();
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/parse?url=https://www.sm%C3%B8rrebr%C3%B8d.fr/&display=true")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    let body_bytes = response.into_body().collect().await.unwrap().to_bytes();
+    let body: serde_json::Value = serde_json::from_slice(&body_bytes).unwrap();
+
+    assert_eq!(body["repertoire"]["label"], "smørrebrød");
+    assert_eq!(body["repertoire"]["tld"], "fr");
+    assert_eq!(body["repertoire"]["disallowed"][0]["position"], 2);
+    assert_eq!(body["repertoire"]["disallowed"][0]["code_point"], "U+00F8");
+    assert_eq!(body["display"]["unicode"], false);
+    assert_eq!(body["display"]["reason"]["kind"], "outside-repertoire");
+}
+
+#[tokio::test]
+async fn test_lookalike_endpoint() {
+    let store = Arc::new(SuffixStore::embedded());
+    let protected = ProtectedDomains::new(&store, ["paypal.com"]).unwrap();
+    let app = create_router_with_state(AppState {
+        store,
+        protected: Arc::new(protected),
+        unwrapper: Arc::new(Unwrapper::builtin()),
+    });
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/lookalike?url=https://login.xn--pypal-4ve.com/")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body_bytes = response.into_body().collect().await.unwrap().to_bytes();
+    let body: LookalikeResponse = serde_json::from_slice(&body_bytes).unwrap();
+
+    let lookalike = body.lookalike.expect("lookalike match");
+    assert_eq!(lookalike.root_domain, "p\u{430}ypal.com");
+    assert_eq!(lookalike.protected_domain, "paypal.com");
+    assert_eq!(lookalike.differences.len(), 1);
+    assert_eq!(lookalike.differences[0].position, 1);
+    assert_eq!(lookalike.differences[0].found, "\u{430}");
+    assert_eq!(lookalike.differences[0].expected, "a");
+    assert_eq!(body.error, None);
+    assert!(body.debug.is_none());
+}
+
+#[tokio::test]
+async fn test_lookalike_endpoint_without_protected_domains() {
+    let app = create_router_with_store(Arc::new(SuffixStore::embedded()));
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/lookalike?url=https://example.com&debug=true")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    let body_bytes = response.into_body().collect().await.unwrap().to_bytes();
+    let body: LookalikeResponse = serde_json::from_slice(&body_bytes).unwrap();
+
+    assert!(body.lookalike.is_none());
+    assert!(body.error.unwrap().contains("PROTECTED_DOMAINS_FILE"));
+    let debug = body.debug.expect("debug block requested");
+    assert_eq!(debug.psl.version.as_deref(), Some("2025-02-27_07-53-30_UTC"));
+}
+
+#[tokio::test]
+async fn test_parse_endpoint_display_decision() {
+    let app = create_router();
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/parse?url=https://login.xn--pple-43d.com/&display=true")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    let body_bytes = response.into_body().collect().await.unwrap().to_bytes();
+    let body: serde_json::Value = serde_json::from_slice(&body_bytes).unwrap();
+
+    assert_eq!(body["display"]["host"], "xn--pple-43d.com");
+    assert_eq!(body["display"]["unicode"], false);
+    assert_eq!(body["display"]["reason"]["kind"], "mixed-script");
+    assert_eq!(body["display"]["reason"]["scripts"], serde_json::json!(["Cyrillic", "Latin"]));
+}
+
+#[tokio::test]
+async fn test_parse_endpoint_idna_options() {
+    let app = create_router();
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/parse?url=https://www.fa%C3%9F.de/&transitional_processing=true")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    let body_bytes = response.into_body().collect().await.unwrap().to_bytes();
+    let body: serde_json::Value = serde_json::from_slice(&body_bytes).unwrap();
+
+    assert_eq!(body["root_domain"], "fass.de");
+    assert!(body["error"].is_null());
+}
+
+#[tokio::test]
+async fn test_parse_endpoint_display_honours_idna_options() {
+    let options = IdnaOptions {
+        use_std3_ascii_rules: true,
+        ..IdnaOptions::default()
+    };
+    let app = create_router_with_store(Arc::new(SuffixStore::embedded().with_idna_options(options)));
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/parse?url=https://www.%C3%A4_b.com/&use_std3_ascii_rules=false&display=true")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    let body_bytes = response.into_body().collect().await.unwrap().to_bytes();
+    let body: serde_json::Value = serde_json::from_slice(&body_bytes).unwrap();
+
+    // Only the store's options reject the underscore; the display decision
+    // follows the request's, like the root domain
+    assert_eq!(body["root_domain"], "ä_b.com");
+    assert_eq!(body["display"]["host"], "ä_b.com");
+}
+
+#[tokio::test]
+async fn test_parse_endpoint_punycode_issues() {
+    let app = create_router();
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/parse?url=https://login.xn--paypal-.com/")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    let body_bytes = response.into_body().collect().await.unwrap().to_bytes();
+    let body: serde_json::Value = serde_json::from_slice(&body_bytes).unwrap();
+
+    assert!(body["root_domain"].is_null());
+    assert!(body["error"].as_str().unwrap().contains("xn--paypal-"));
+    assert_eq!(body["punycode"][0]["label"], "xn--paypal-");
+    assert_eq!(body["punycode"][0]["decoded"], "paypal");
+    assert_eq!(body["punycode"][0]["problem"], "ascii-only");
+}
+
+#[tokio::test]
+async fn test_parse_endpoint_normalizations() {
+    let app = create_router();
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/parse?url=https://user@WWW.Example.COM:8443/&normalizations=true")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    let body_bytes = response.into_body().collect().await.unwrap().to_bytes();
+    let body: serde_json::Value = serde_json::from_slice(&body_bytes).unwrap();
+
+    assert_eq!(body["root_domain"], "example.com");
+    let kinds: Vec<&str> = body["normalizations"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|n| n["kind"].as_str().unwrap())
+        .collect();
+    assert_eq!(kinds, vec!["userinfo-removed", "port-removed", "lowercased"]);
+    assert_eq!(body["normalizations"][2]["before"], "WWW.Example.COM");
+    assert_eq!(body["normalizations"][2]["after"], "www.example.com");
+}
+
+#[tokio::test]
+async fn test_parse_endpoint_omits_normalizations_by_default() {
+    let app = create_router();
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/parse?url=https://WWW.Example.COM/")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    let body_bytes = response.into_body().collect().await.unwrap().to_bytes();
+    let body: serde_json::Value = serde_json::from_slice(&body_bytes).unwrap();
+
+    assert!(body.get("normalizations").is_none());
+}
+
+#[tokio::test]
+async fn test_parse_endpoint_diagnostics() {
+    let app = create_router();
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/parse?url=htp:/exa%20mple..com")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    let body_bytes = response.into_body().collect().await.unwrap().to_bytes();
+    let body: serde_json::Value = serde_json::from_slice(&body_bytes).unwrap();
+
+    assert!(body["error"].is_string());
+    assert_eq!(body["diagnostics"][0]["kind"], "misspelled-scheme");
+    assert_eq!(body["diagnostics"][0]["span"], serde_json::json!({"start": 0, "end": 3}));
+    assert_eq!(body["diagnostics"][0]["suggestion"], "http");
+    assert_eq!(body["diagnostics"][2]["kind"], "whitespace");
+    assert_eq!(body["suggestion"], "http://example.com");
+}
+
+#[tokio::test]
+async fn test_parse_endpoint_refang_and_defang() {
+    let app = create_router();
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/parse?url=hxxps://login.evil%5B.%5Dexample%5B.%5Dcom/&refang=true&defang=true")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    let body_bytes = response.into_body().collect().await.unwrap().to_bytes();
+    let body: serde_json::Value = serde_json::from_slice(&body_bytes).unwrap();
+
+    assert_eq!(body["original_url"], "hxxps://login.evil[.]example[.]com/");
+    assert_eq!(body["refanged_url"], "https://login.evil.example.com/");
+    assert_eq!(body["root_domain"], "example.com");
+    assert_eq!(body["defanged_url"], "hxxps://login[.]evil[.]example[.]com/");
+    assert_eq!(body["defanged_root_domain"], "example[.]com");
+}
+
+#[tokio::test]
+async fn test_parse_endpoint_without_refang() {
+    let app = create_router();
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/parse?url=hxxps://evil%5B.%5Dexample%5B.%5Dcom/")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    let body_bytes = response.into_body().collect().await.unwrap().to_bytes();
+    let body: serde_json::Value = serde_json::from_slice(&body_bytes).unwrap();
+
+    assert!(body["root_domain"].is_null());
+    assert!(body.get("refanged_url").is_none());
+    assert!(body.get("defanged_url").is_none());
+}
+
+#[tokio::test]
+async fn test_parse_endpoint_unwrap() {
+    let app = create_router();
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/parse?url=https://l.facebook.com/l.php%3Fu%3Dhttps%253A%252F%252Fwww.example.net%252F&unwrap=true")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    let body_bytes = response.into_body().collect().await.unwrap().to_bytes();
+    let body: serde_json::Value = serde_json::from_slice(&body_bytes).unwrap();
+
+    assert_eq!(body["root_domain"], "example.net");
+    assert_eq!(body["redirect_chain"][0]["root_domain"], "facebook.com");
+    assert_eq!(body["redirect_chain"][0]["rule"], "facebook");
+    assert_eq!(body["redirect_chain"][1]["url"], "https://www.example.net/");
+    assert!(body["redirect_chain"][1]["rule"].is_null());
+}
+
+#[tokio::test]
+async fn test_parse_endpoint_unwrap_error() {
+    let app = create_router();
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/parse?url=not%20a%20url&unwrap=true")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    let body_bytes = response.into_body().collect().await.unwrap().to_bytes();
+    let body: serde_json::Value = serde_json::from_slice(&body_bytes).unwrap();
+
+    assert!(body["root_domain"].is_null());
+    assert_eq!(body["error"], "Could not follow redirects: Invalid URL format");
+    assert!(body.get("redirect_chain").is_none());
+}
+
+#[tokio::test]
+async fn test_parse_endpoint_refang_and_unwrap() {
+    let app = create_router();
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/parse?url=hxxps://www.google[.]com/url%3Fq%3Dhttps://example.org/&refang=true&unwrap=true")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    let body_bytes = response.into_body().collect().await.unwrap().to_bytes();
+    let body: serde_json::Value = serde_json::from_slice(&body_bytes).unwrap();
+
+    // The refanged URL is the input, not the destination
+    assert_eq!(body["refanged_url"], "https://www.google.com/url?q=https://example.org/");
+    assert_eq!(body["root_domain"], "example.org");
+    assert_eq!(body["redirect_chain"][0]["url"], body["refanged_url"]);
+}
@@ -0,0 +1,138 @@
+use domain_validator::batch::{self, error_kind, BatchProgress};
+use domain_validator::suffix_store::SuffixStore;
+use std::io::Cursor;
+
+fn csv(input: &str, column: &str) -> Result<(String, batch::BatchSummary), String> {
+    let mut output = Vec::new();
+    let summary = batch::annotate_csv(&SuffixStore::embedded(), input.as_bytes(), &mut output, column, |_| {})?;
+    Ok((String::from_utf8(output).unwrap(), summary))
+}
+
+fn jsonl(input: &str, pointer: &str) -> Result<(String, batch::BatchSummary), String> {
+    let mut output = Vec::new();
+    let summary =
+        batch::annotate_jsonl(&SuffixStore::embedded(), Cursor::new(input), &mut output, pointer, |_| {})?;
+    Ok((String::from_utf8(output).unwrap(), summary))
+}
+
+#[test]
+fn test_csv_appends_columns() {
+    let input = "id,link,note\n\
+                 1,https://www.example.co.uk/path,\"quoted, with comma\"\n\
+                 2,co.uk,\n\
+                 3,,empty\n";
+    let (output, summary) = csv(input, "link").unwrap();
+
+    assert_eq!(
+        output,
+        "id,link,note,root_domain,suffix,error\n\
+         1,https://www.example.co.uk/path,\"quoted, with comma\",example.co.uk,co.uk,\n\
+         2,co.uk,,,,Could not extract root domain from 'co.uk'\n\
+         3,,empty,,,Missing URL: the value is empty\n"
+    );
+    assert_eq!(summary.records, 3);
+    assert_eq!(summary.succeeded(), 1);
+    assert_eq!(summary.failed(), 2);
+    assert_eq!(summary.errors["no root domain"], 1);
+    assert_eq!(summary.errors["missing URL"], 1);
+}
+
+#[test]
+fn test_csv_errors() {
+    assert_eq!(csv("id,url\n1,example.com\n", "link").unwrap_err(), "CSV input has no 'link' column");
+    assert_eq!(
+        csv("url,error\nexample.com,\n", "url").unwrap_err(),
+        "CSV input already has a 'error' column"
+    );
+}
+
+#[test]
+fn test_csv_reports_ragged_records_one_by_one() {
+    let input = "id,note,url\n\
+                 1,short\n\
+                 2\n\
+                 3,long,me.github.io,extra\n\
+                 4,,www.example.com\n";
+    let (output, summary) = csv(input, "url").unwrap();
+
+    assert_eq!(
+        output,
+        "id,note,url,root_domain,suffix,error\n\
+         1,short,,,,Missing URL: record has no such column\n\
+         2,,,,,Missing URL: record has no such column\n\
+         3,long,me.github.io,extra,me.github.io,github.io,\n\
+         4,,www.example.com,example.com,com,\n"
+    );
+    assert_eq!(summary.records, 4);
+    assert_eq!(summary.errors["missing URL"], 2);
+}
+
+#[test]
+fn test_jsonl_appends_fields_and_keeps_the_rest_of_the_line() {
+    let input = "{\"b\": 1.0e3, \"a\": {\"url\": \"https://me.github.io\"}}\r\n\
+                 \n\
+                 {}\n\
+                 {\"a\":{\"url\":42}}\n\
+                 {\"a\":{\"url\":\"http://[::1]/\"}}";
+    let (output, summary) = jsonl(input, "/a/url").unwrap();
+
+    assert_eq!(
+        output,
+        "{\"b\": 1.0e3, \"a\": {\"url\": \"https://me.github.io\"},\"root_domain\":\"me.github.io\",\"suffix\":\"github.io\",\"error\":null}\n\
+         \n\
+         {\"root_domain\":null,\"suffix\":null,\"error\":\"Missing URL: no value at '/a/url'\"}\n\
+         {\"a\":{\"url\":42},\"root_domain\":null,\"suffix\":null,\"error\":\"Missing URL: '/a/url' is not a string\"}\n\
+         {\"a\":{\"url\":\"http://[::1]/\"},\"root_domain\":null,\"suffix\":null,\"error\":\"IPv6 addresses are not valid domains\"}\n"
+    );
+    for line in output.lines().filter(|line| !line.is_empty()) {
+        serde_json::from_str::<serde_json::Value>(line).unwrap();
+    }
+    assert_eq!(summary.records, 4);
+    assert_eq!(summary.succeeded(), 1);
+    assert_eq!(summary.errors["missing URL"], 2);
+    assert_eq!(summary.errors["IP address"], 1);
+}
+
+#[test]
+fn test_jsonl_errors() {
+    assert!(jsonl("{\"url\": \"example.com\"}\n", "url").unwrap_err().contains("Invalid JSON pointer"));
+    assert_eq!(jsonl("{\"url\": \"a.com\"}\n[1]\n", "/url").unwrap_err(), "line 2: expected a JSON object");
+    assert!(jsonl("{\"url\": \n", "/url").unwrap_err().starts_with("line 1: invalid JSON"));
+    assert_eq!(
+        jsonl("{\"url\": \"a.com\", \"suffix\": \"com\"}\n", "/url").unwrap_err(),
+        "line 1: input already has a 'suffix' field"
+    );
+}
+
+#[test]
+fn test_progress_is_reported_per_record() {
+    let input = "url\nexample.com\nexample.org\n";
+    let mut reports = Vec::new();
+    let mut output = Vec::new();
+    batch::annotate_csv(&SuffixStore::embedded(), input.as_bytes(), &mut output, "url", |progress| {
+        reports.push(progress)
+    })
+    .unwrap();
+
+    assert_eq!(reports.len(), 2);
+    assert_eq!(reports[1], BatchProgress { records: 2, bytes_read: input.len() as u64 });
+}
+
+#[test]
+fn test_summary_display() {
+    let (_, summary) = csv("url\nexample.com\n192.168.1.1\nnot a url\n", "url").unwrap();
+
+    assert_eq!(
+        summary.to_string(),
+        "Processed 3 records: 1 succeeded, 2 failed\n  IP address: 1\n  invalid URL: 1\n"
+    );
+}
+
+#[test]
+fn test_error_kinds() {
+    assert_eq!(error_kind("Invalid URL format"), "invalid URL");
+    assert_eq!(error_kind("URL has no valid host component"), "invalid URL");
+    assert_eq!(error_kind("IPv4 addresses are not valid domains"), "IP address");
+    assert_eq!(error_kind("Invalid domain name: 'a\u{FFFD}.com'"), "invalid domain");
+    assert_eq!(error_kind("Could not extract root domain from 'com'"), "no root domain");
+}
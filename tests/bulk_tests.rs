@@ -0,0 +1,55 @@
+use domain_validator::suffix_store::SuffixStore;
+
+#[test]
+fn test_results_match_sequential_extraction_in_order() {
+    let store = SuffixStore::embedded();
+    let urls: Vec<String> = (0..5000)
+        .map(|i| match i % 5 {
+            0 => format!("https://www{}.example.co.uk/path", i),
+            1 => format!("http://site{}.github.io", i),
+            2 => format!("http://192.168.{}.{}/", i % 256, i / 256),
+            3 => "not a valid url".to_string(),
+            _ => format!("shop{}.example.com", i),
+        })
+        .collect();
+
+    let results = store.extract_root_domains_from_urls_par(&urls);
+
+    assert_eq!(results.len(), urls.len());
+    for (url, result) in urls.iter().zip(&results) {
+        assert_eq!(result, &store.extract_root_domain_from_url(url), "{}", url);
+    }
+}
+
+#[test]
+fn test_errors_are_reported_per_item() {
+    let store = SuffixStore::embedded();
+    let domains = ["www.example.com", "co.uk", "www.\u{FFFD}.com", "blog.example.co.uk"];
+
+    let results = store.extract_root_domains_par(domains);
+
+    assert_eq!(results[0], Ok("example.com".to_string()));
+    assert_eq!(results[1], Err("Could not extract root domain from 'co.uk'".to_string()));
+    assert!(results[2].as_ref().unwrap_err().starts_with("Invalid domain name"));
+    assert_eq!(results[3], Ok("example.co.uk".to_string()));
+}
+
+#[test]
+fn test_accepts_iterators() {
+    let store = SuffixStore::embedded();
+
+    let results = store.extract_root_domains_par((0..3).map(|i| format!("a.b{}.example.com", i)));
+    assert_eq!(results, vec![Ok("example.com".to_string()); 3]);
+
+    let results = store.extract_root_domains_from_urls_par(Vec::<String>::new());
+    assert!(results.is_empty());
+}
+
+#[test]
+fn test_runs_in_a_custom_thread_pool() {
+    let store = SuffixStore::embedded();
+    let pool = rayon::ThreadPoolBuilder::new().num_threads(2).build().unwrap();
+
+    let results = pool.install(|| store.extract_root_domains_from_urls_par(["https://x.example.org", "https://y.example.net"]));
+    assert_eq!(results, vec![Ok("example.org".to_string()), Ok("example.net".to_string())]);
+}
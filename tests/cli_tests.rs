@@ -0,0 +1,170 @@
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+const BIN: &str = env!("CARGO_BIN_EXE_domain-validator");
+
+fn run(args: &[&str], stdin: Option<&str>) -> Output {
+    let mut child = Command::new(BIN)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut input = child.stdin.take().unwrap();
+    if let Some(stdin) = stdin {
+        input.write_all(stdin.as_bytes()).unwrap();
+    }
+    drop(input);
+    child.wait_with_output().unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+#[test]
+fn test_parse_arguments_plain() {
+    let output = run(&["parse", "https://www.example.co.uk/path", "http://user@blog.example.com:8080"], None);
+
+    assert!(output.status.success());
+    assert_eq!(stdout(&output), "example.co.uk\nexample.com\n");
+}
+
+#[test]
+fn test_parse_invalid_input_fails() {
+    let output = run(&["parse", "https://example.com", "http://192.168.1.1/"], None);
+
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(stdout(&output), "example.com\n");
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("http://192.168.1.1/: IPv4 addresses are not valid domains"));
+}
+
+#[test]
+fn test_parse_reads_stdin() {
+    let output = run(&["parse"], Some("https://www.example.com\n\n  test.github.io  \n"));
+    assert!(output.status.success());
+    assert_eq!(stdout(&output), "example.com\ntest.github.io\n");
+
+    let output = run(&["parse", "-"], Some("www.example.co.uk\n"));
+    assert_eq!(stdout(&output), "example.co.uk\n");
+}
+
+#[test]
+fn test_parse_tsv() {
+    let output = run(&["parse", "--format", "tsv", "https://www.example.com", "not a url"], None);
+
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        stdout(&output),
+        "input\troot_domain\terror\n\
+         https://www.example.com\texample.com\t\n\
+         not a url\t\tInvalid URL format\n"
+    );
+}
+
+#[test]
+fn test_parse_full_tsv() {
+    let output = run(&["parse", "-f", "tsv", "--full", "https://a.b.example.co.uk"], None);
+
+    assert!(output.status.success());
+    assert_eq!(
+        stdout(&output),
+        "input\thost\troot_domain\tsuffix\tsubdomain\tsection\terror\n\
+         https://a.b.example.co.uk\ta.b.example.co.uk\texample.co.uk\tco.uk\ta.b\ticann\t\n"
+    );
+}
+
+#[test]
+fn test_parse_json_lines() {
+    let output = run(&["parse", "--format", "json", "--full", "https://me.github.io", "co.uk"], None);
+    assert_eq!(output.status.code(), Some(1));
+
+    let lines: Vec<serde_json::Value> =
+        stdout(&output).lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[0]["input"], "https://me.github.io");
+    assert_eq!(lines[0]["root_domain"], "me.github.io");
+    assert_eq!(lines[0]["suffix"], "github.io");
+    assert_eq!(lines[0]["section"], "private");
+    assert_eq!(lines[0]["subdomain"], serde_json::Value::Null);
+    assert_eq!(lines[0]["error"], serde_json::Value::Null);
+    assert_eq!(lines[1]["root_domain"], serde_json::Value::Null);
+    assert!(lines[1]["error"].as_str().unwrap().contains("co.uk"));
+}
+
+#[test]
+fn test_parse_full_plain() {
+    let output = run(&["parse", "--full", "https://www.example.co.uk"], None);
+
+    assert!(output.status.success());
+    assert_eq!(
+        stdout(&output),
+        "https://www.example.co.uk\n  host: www.example.co.uk\n  root_domain: example.co.uk\n  \
+         suffix: co.uk\n  subdomain: www\n  section: icann\n"
+    );
+}
+
+#[test]
+fn test_parse_with_custom_list() {
+    let dir = std::env::temp_dir().join(format!("dv-cli-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("list.dat");
+    std::fs::write(&path, "// ===BEGIN ICANN DOMAINS===\ncom\nexample.com\n// ===END ICANN DOMAINS===\n").unwrap();
+
+    let output = run(&["parse", "--psl", path.to_str().unwrap(), "https://a.b.example.com"], None);
+    assert!(output.status.success());
+    assert_eq!(stdout(&output), "b.example.com\n");
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_usage_errors() {
+    assert_eq!(run(&[], None).status.code(), Some(2));
+    assert_eq!(run(&["parse", "--format", "xml", "example.com"], None).status.code(), Some(2));
+    assert_eq!(run(&["psl-compile", "only-one-arg"], None).status.code(), Some(2));
+}
+
+#[test]
+fn test_batch_csv_from_stdin() {
+    let output = run(&["batch", "-", "--format", "csv", "--column", "link"], Some("link\nhttps://www.example.com\nco.uk\n"));
+
+    assert!(output.status.success());
+    assert_eq!(
+        stdout(&output),
+        "link,root_domain,suffix,error\nhttps://www.example.com,example.com,com,\nco.uk,,,Could not extract root domain from 'co.uk'\n"
+    );
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("Processed 2 records: 1 succeeded, 1 failed"));
+    assert!(stderr.contains("no root domain: 1"));
+}
+
+#[test]
+fn test_batch_jsonl_file_to_file() {
+    let dir = std::env::temp_dir().join(format!("dv-cli-batch-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let input = dir.join("input.jsonl");
+    let output_path = dir.join("output.jsonl");
+    std::fs::write(&input, "{\"req\":{\"url\":\"https://a.example.co.uk\"}}\n").unwrap();
+
+    let output = run(
+        &["batch", input.to_str().unwrap(), "-o", output_path.to_str().unwrap(), "--pointer", "/req/url", "-q"],
+        None,
+    );
+    assert!(output.status.success());
+    assert_eq!(
+        std::fs::read_to_string(&output_path).unwrap(),
+        "{\"req\":{\"url\":\"https://a.example.co.uk\"},\"root_domain\":\"example.co.uk\",\"suffix\":\"co.uk\",\"error\":null}\n"
+    );
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_batch_argument_errors() {
+    assert_eq!(run(&["batch", "-"], Some("url\n")).status.code(), Some(1));
+    assert_eq!(run(&["batch", "-", "-f", "csv", "--pointer", "/url"], Some("url\n")).status.code(), Some(1));
+    assert_eq!(run(&["batch", "-", "-f", "jsonl", "--column", "url"], Some("")).status.code(), Some(1));
+}
@@ -0,0 +1,92 @@
+use domain_validator::defang::{defang, refang};
+use domain_validator::domain_parser;
+
+#[test]
+fn test_refang_common_conventions() {
+    for (defanged, refanged) in [
+        ("hxxps://evil[.]example[.]com/login", "https://evil.example.com/login"),
+        ("hXXp://evil(.)example{.}com", "http://evil.example.com"),
+        ("evil(dot)com", "evil.com"),
+        ("evil[DOT]com", "evil.com"),
+        ("evil[ . ]com", "evil.com"),
+        ("evil [dot] com", "evil.com"),
+        ("www (.) evil\t[ . ]  com/a b", "www.evil.com/a b"),
+        ("evil\\.com", "evil.com"),
+        ("https[:]//evil.com", "https://evil.com"),
+        ("hxxps[://]evil.com[/]path", "https://evil.com/path"),
+        ("fxp://files[.]evil[.]com", "ftp://files.evil.com"),
+        ("user[at]evil[.]com", "user@evil.com"),
+    ] {
+        assert_eq!(refang(defanged), refanged, "{}", defanged);
+    }
+}
+
+#[test]
+fn test_refang_keeps_other_text() {
+    for text in [
+        "https://www.example.com/path?q=(a)&r=[b]",
+        "https://[::1]:8080/",
+        "https://example.com/hxxp/ahxxp:x",
+        "münchen.de",
+        "",
+    ] {
+        assert_eq!(refang(text), text);
+    }
+}
+
+#[test]
+fn test_refanged_urls_parse() {
+    assert!(domain_parser::parse_url("hxxps://evil[.]example[.]com").is_err());
+    assert_eq!(
+        domain_parser::extract_root_domain_from_url(&refang("hxxps://login.evil[.]example[.]co[.]uk/")),
+        Ok("example.co.uk".to_string())
+    );
+}
+
+#[test]
+fn test_defang() {
+    assert_eq!(defang("https://evil.example.com/login.php"), "hxxps://evil[.]example[.]com/login.php");
+    assert_eq!(defang("HTTP://user@evil.com:8080/?next=a.b"), "hxxp://user@evil[.]com:8080/?next=a.b");
+    assert_eq!(defang("ftp://files.evil.com"), "fxp://files[.]evil[.]com");
+    assert_eq!(defang("evil.example.com"), "evil[.]example[.]com");
+    assert_eq!(defang("wss://evil.com/socket"), "wss://evil[.]com/socket");
+
+    // Defanging is idempotent and refanging undoes it
+    let defanged = defang("https://evil.example.com/login");
+    assert_eq!(defang(&defanged), defanged);
+    assert_eq!(refang(&defanged), "https://evil.example.com/login");
+}
+
+#[test]
+fn test_defang_keeps_the_path_and_query() {
+    assert_eq!(defang("https://evil.com/p(at)h"), "hxxps://evil[.]com/p(at)h");
+    assert_eq!(defang("https://evil.com/a[.]b/c\\.d?q=x[dot]y#(.)"), "hxxps://evil[.]com/a[.]b/c\\.d?q=x[dot]y#(.)");
+    assert_eq!(defang("hxxps[://]evil[.]com[/]p(at)h"), "hxxps://evil[.]com[/]p(at)h");
+    assert_eq!(defang("evil.com:8080/v1.2"), "evil[.]com:8080/v1.2");
+}
+
+#[test]
+fn test_defang_leading_slashes_start_the_host() {
+    assert_eq!(defang("//evil.com/login.php"), "//evil[.]com/login.php");
+    assert_eq!(defang("\\\\evil.com\\share.txt"), "\\\\evil[.]com\\share.txt");
+    assert_eq!(defang("https:\\\\evil.com"), "hxxps:\\\\evil[.]com");
+}
+
+#[test]
+fn test_defang_idna_full_stops() {
+    assert_eq!(defang("https://evil\u{3002}example\u{FF0E}co\u{FF61}uk/"), "hxxps://evil[.]example[.]co[.]uk/");
+    assert_eq!(defang("evil\u{3002}com/\u{3002}"), "evil[.]com/\u{3002}");
+}
+
+#[test]
+fn test_defang_embedded_urls() {
+    assert_eq!(
+        defang("https://login.example.com/?u=https://other.com/a.b&next=HTTP://x.y.z"),
+        "hxxps://login[.]example[.]com/?u=hxxps://other[.]com/a.b&next=hxxp://x[.]y[.]z"
+    );
+    assert_eq!(defang("example.com/r/ftp://files.evil.com/x"), "example[.]com/r/fxp://files[.]evil[.]com/x");
+
+    let defanged = defang("https://a.com/?u=https://b.com/?v=https://c.com");
+    assert_eq!(defanged, "hxxps://a[.]com/?u=hxxps://b[.]com/?v=hxxps://c[.]com");
+    assert_eq!(defang(&defanged), defanged);
+}
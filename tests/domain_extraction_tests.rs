@@ -71,8 +71,12 @@ fn test_extract_root_domain_idn_mixed_case() {
 
 #[test]
 fn test_extract_root_domain_idn_non_latin() {
+    // 東京.jp is itself a public suffix
     let result = domain_parser::extract_root_domain("www.東京.jp");
-    assert_eq!(result, Ok("東京.jp".to_string()));
+    assert_eq!(result, Ok("www.東京.jp".to_string()));
+
+    let result = domain_parser::extract_root_domain("a.www.東京.jp");
+    assert_eq!(result, Ok("www.東京.jp".to_string()));
 }
 
 #[test]
@@ -81,6 +85,17 @@ fn test_extract_root_domain_idn_with_accents() {
     assert_eq!(result, Ok("académie-française.fr".to_string()));
 }
 
+#[test]
+fn test_extract_root_domain_idn_display_policy() {
+    // Cyrillic а mixed with Latin is shown in punycode
+    let result = domain_parser::extract_root_domain("www.\u{430}pple.com");
+    assert_eq!(result, Ok("xn--pple-43d.com".to_string()));
+
+    // A single-script Cyrillic name is shown in Unicode
+    let result = domain_parser::extract_root_domain("www.пример.рф");
+    assert_eq!(result, Ok("пример.рф".to_string()));
+}
+
 #[test]
 fn test_extract_root_domain_idn_invalid() {
     // Test with an invalid IDN
@@ -101,6 +116,22 @@ fn test_extract_root_domain_from_idn_url() {
     assert_eq!(result, Ok("münchen.de".to_string()));
 }
 
+#[test]
+fn test_extract_root_domain_from_punycode_url() {
+    let result = domain_parser::extract_root_domain_from_url("https://www.xn--mnchen-3ya.de/müller");
+    assert_eq!(result, Ok("xn--mnchen-3ya.de".to_string()));
+
+    let result = domain_parser::extract_root_domain_from_url("https://www.MÜNCHEN.de/");
+    assert_eq!(result, Ok("münchen.de".to_string()));
+
+    // Only the host as written decides, not a path or query repeating its label
+    let result = domain_parser::extract_root_domain_from_url("https://www.xn--mnchen-3ya.de/münchen");
+    assert_eq!(result, Ok("xn--mnchen-3ya.de".to_string()));
+
+    let result = domain_parser::extract_root_domain_from_url("https://xn--mnchen-3ya.de/?city=München#münchen");
+    assert_eq!(result, Ok("xn--mnchen-3ya.de".to_string()));
+}
+
 #[test]
 fn test_extract_root_domain_from_invalid_url() {
     let result = domain_parser::extract_root_domain_from_url("not a valid url");
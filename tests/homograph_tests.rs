@@ -0,0 +1,73 @@
+use domain_validator::homograph::{analyze, is_idn, RestrictionLevel, Risk};
+
+#[test]
+fn test_ascii_host_is_low_risk() {
+    let report = analyze("www.example.com");
+    assert_eq!(report.risk, Risk::Low);
+    assert_eq!(report.labels.len(), 3);
+    assert_eq!(report.labels[1].restriction_level, RestrictionLevel::AsciiOnly);
+    assert_eq!(report.labels[1].scripts, vec!["Latin"]);
+    assert!(!report.labels[1].mixed_script);
+}
+
+#[test]
+fn test_mixed_cyrillic_and_latin_is_high_risk() {
+    // Cyrillic а followed by Latin pple
+    let report = analyze("\u{430}pple.com");
+    assert_eq!(report.risk, Risk::High);
+    assert_eq!(report.host, "\u{430}pple.com");
+
+    let label = &report.labels[0];
+    assert!(label.mixed_script);
+    assert_eq!(label.scripts, vec!["Cyrillic", "Latin"]);
+    assert_eq!(label.restriction_level, RestrictionLevel::MinimallyRestrictive);
+    assert_eq!(label.skeleton, analyze("apple.com").labels[0].skeleton);
+    assert_eq!(report.labels[1].risk, Risk::Low);
+}
+
+#[test]
+fn test_punycode_is_decoded() {
+    let report = analyze("xn--pple-43d.com");
+    assert_eq!(report.host, "\u{430}pple.com");
+    assert_eq!(report.risk, Risk::High);
+}
+
+#[test]
+fn test_whole_script_confusable() {
+    // All-Cyrillic аррӏе
+    let report = analyze("\u{430}\u{440}\u{440}\u{4cf}\u{435}.com");
+    let label = &report.labels[0];
+    assert_eq!(label.scripts, vec!["Cyrillic"]);
+    assert_eq!(label.restriction_level, RestrictionLevel::SingleScript);
+    assert!(!label.mixed_script);
+    assert!(label.whole_script_confusable);
+    assert_eq!(report.risk, Risk::High);
+}
+
+#[test]
+fn test_legitimate_idns_are_low_risk() {
+    for host in ["münchen.de", "東京.jp", "пример.рф", "ελληνικά.gr", "日本語とカタカナ.jp"] {
+        let report = analyze(host);
+        assert_eq!(report.risk, Risk::Low, "{}", host);
+        assert!(report.labels.iter().all(|label| !label.whole_script_confusable), "{}", host);
+    }
+
+    let japanese = analyze("abc東京.jp");
+    assert_eq!(japanese.labels[0].restriction_level, RestrictionLevel::HighlyRestrictive);
+    assert_eq!(japanese.labels[0].scripts, vec!["Latin", "Han"]);
+    assert_eq!(japanese.risk, Risk::Low);
+}
+
+#[test]
+fn test_latin_with_other_script_is_medium_risk() {
+    let report = analyze("shopعربي.com");
+    assert_eq!(report.labels[0].restriction_level, RestrictionLevel::ModeratelyRestrictive);
+    assert_eq!(report.risk, Risk::Medium);
+}
+
+#[test]
+fn test_is_idn() {
+    assert!(is_idn("münchen.de"));
+    assert!(is_idn("www.XN--mnchen-3ya.de"));
+    assert!(!is_idn("www.example.com"));
+}
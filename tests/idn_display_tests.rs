@@ -0,0 +1,126 @@
+use domain_validator::homograph::analyze;
+use domain_validator::idn_display::{display_host, display_root_domain, DisplayReason};
+use domain_validator::suffix_store::SuffixStore;
+
+#[test]
+fn test_ascii_hosts() {
+    let decision = display_host("www.Example.com");
+    assert_eq!(decision.host, "www.example.com");
+    assert!(!decision.unicode);
+    assert_eq!(decision.reason, DisplayReason::Ascii);
+}
+
+#[test]
+fn test_allowed_hosts_are_shown_in_unicode() {
+    for (host, expected) in [
+        ("xn--mnchen-3ya.de", "münchen.de"),
+        ("münchen.de", "münchen.de"),
+        ("www.東京.jp", "www.東京.jp"),
+        ("пример.рф", "пример.рф"),
+        ("shopعربي.com", "shopعربي.com"),
+    ] {
+        let decision = display_host(host);
+        assert!(decision.unicode, "{}: {}", host, decision.reason);
+        assert_eq!(decision.host, expected);
+        assert_eq!(decision.reason, DisplayReason::Allowed);
+    }
+}
+
+#[test]
+fn test_mixed_script_falls_back_to_punycode() {
+    let decision = display_host("\u{430}pple.com");
+    assert_eq!(decision.host, "xn--pple-43d.com");
+    assert!(!decision.unicode);
+    assert_eq!(
+        decision.reason,
+        DisplayReason::MixedScript {
+            label: "\u{430}pple".to_string(),
+            scripts: vec!["Cyrillic".to_string(), "Latin".to_string()],
+        }
+    );
+    assert!(decision.reason.to_string().contains("mixes scripts Cyrillic, Latin"));
+}
+
+#[test]
+fn test_whole_script_confusable_depends_on_tld() {
+    // All-Cyrillic аррӏе
+    let label = "\u{430}\u{440}\u{440}\u{4cf}\u{435}";
+
+    let com = display_host(&format!("{}.com", label));
+    assert!(!com.unicode);
+    assert!(com.host.starts_with("xn--"));
+    assert_eq!(
+        com.reason,
+        DisplayReason::WholeScriptConfusable {
+            label: label.to_string(),
+            script: "Cyrillic".to_string(),
+            tld: "com".to_string(),
+        }
+    );
+
+    let ru = display_host(&format!("{}.ru", label));
+    assert!(ru.unicode);
+    assert_eq!(ru.host, format!("{}.ru", label));
+
+    // A TLD with a table allows the labels its repertoire covers, whatever
+    // the script list says: ӏ is not a Russian letter
+    let rf = display_host(&format!("{}.рф", label));
+    assert!(!rf.unicode);
+    assert!(matches!(rf.reason, DisplayReason::WholeScriptConfusable { .. }));
+    let pop = "\u{440}\u{43e}\u{440}.рф";
+    assert!(analyze(pop).labels[0].whole_script_confusable);
+    assert!(display_host(pop).unicode);
+    assert!(!display_host(&format!("{}.de", label)).unicode);
+}
+
+#[test]
+fn test_disallowed_character_falls_back_to_punycode() {
+    // U+2665 BLACK HEART SUIT is valid IDNA but not an identifier character
+    let decision = display_host("i\u{2665}.com");
+    assert!(!decision.unicode);
+    assert_eq!(
+        decision.reason,
+        DisplayReason::DisallowedCharacter {
+            label: "i\u{2665}".to_string(),
+            character: '\u{2665}',
+        }
+    );
+}
+
+#[test]
+fn test_display_root_domain_records_reason() {
+    let store = SuffixStore::global().unwrap();
+
+    let decision = store.display_root_domain("www.xn--mnchen-3ya.de").unwrap();
+    assert_eq!(decision.host, "münchen.de");
+    assert_eq!(decision.reason, DisplayReason::Allowed);
+
+    let decision = store.display_root_domain("login.xn--pple-43d.com").unwrap();
+    assert_eq!(decision.host, "xn--pple-43d.com");
+    assert!(matches!(decision.reason, DisplayReason::MixedScript { .. }));
+
+    assert!(store.display_root_domain("not a domain").is_err());
+}
+
+#[test]
+fn test_outside_repertoire_falls_back_to_punycode() {
+    // ø passes every display check, but is not in the .fr repertoire
+    let decision = display_root_domain("smørrebrød.fr");
+    assert!(!decision.unicode);
+    assert_eq!(decision.host, "xn--smrrebrd-64af.fr");
+    let DisplayReason::OutsideRepertoire { label, tld, disallowed } = &decision.reason else {
+        panic!("{:?}", decision.reason);
+    };
+    assert_eq!((label.as_str(), tld.as_str()), ("smørrebrød", "fr"));
+    let positions: Vec<usize> = disallowed.iter().map(|c| c.position).collect();
+    assert_eq!(positions, vec![2, 8]);
+    assert!(decision.reason.to_string().contains("outside the 'fr' repertoire: U+00F8, U+00F8"));
+
+    // Only the registrable label of a root domain is checked
+    assert!(display_host("smørrebrød.fr").unicode);
+    assert!(display_root_domain("smørrebrød.dk").unicode);
+
+    let store = SuffixStore::global().unwrap();
+    assert_eq!(store.extract_root_domain("www.smørrebrød.fr"), Ok("xn--smrrebrd-64af.fr".to_string()));
+    assert_eq!(store.extract_root_domain("www.smørrebrød.dk"), Ok("smørrebrød.dk".to_string()));
+}
@@ -0,0 +1,90 @@
+use domain_validator::idn_tables::{DisallowedCodePoint, IdnTables, Repertoire};
+use domain_validator::suffix_store::SuffixStore;
+
+#[test]
+fn test_builtin_tables_load() {
+    let tables = IdnTables::builtin();
+    let tlds: Vec<&str> = tables.tlds().collect();
+    for tld in ["at", "ch", "de", "dk", "fr", "jp", "li", "xn--p1ai"] {
+        assert!(tlds.contains(&tld), "{}", tld);
+    }
+
+    let de = tables.get("de").unwrap();
+    assert!(de.contains('ü'));
+    assert!(de.contains('ß'));
+    assert!(de.contains('a'));
+    assert!(!de.contains('÷'));
+    assert!(!de.contains('я'));
+}
+
+#[test]
+fn test_allowed_labels() {
+    let store = SuffixStore::global().unwrap();
+    let tables = IdnTables::builtin();
+
+    for host in ["www.münchen.de", "xn--mnchen-3ya.de", "example.de", "東京都庁.jp", "カタカナ.jp", "пример.рф"] {
+        let report = tables.check(store, host).unwrap().unwrap();
+        assert!(report.is_valid(), "{}: {:?}", host, report.disallowed);
+    }
+}
+
+#[test]
+fn test_disallowed_code_points_are_reported_with_position() {
+    let store = SuffixStore::global().unwrap();
+    let tables = IdnTables::builtin();
+
+    // Cyrillic а is valid IDNA but not in the .de repertoire
+    let report = tables.check(store, "www.b\u{430}nk.de").unwrap().unwrap();
+    assert_eq!(report.label, "b\u{430}nk");
+    assert_eq!(report.tld, "de");
+    assert!(!report.is_valid());
+    assert_eq!(
+        report.disallowed,
+        vec![DisallowedCodePoint { position: 1, character: '\u{430}', code_point: "U+0430".to_string() }]
+    );
+
+    // .рф labels may not use Latin letters
+    let report = tables.check(store, "shop.рф").unwrap().unwrap();
+    assert_eq!(report.tld, "xn--p1ai");
+    assert_eq!(report.disallowed.len(), 4);
+    assert_eq!(report.disallowed[0].position, 0);
+    assert_eq!(report.disallowed[0].code_point, "U+0073");
+
+    // æ is allowed in .dk but not in .fr
+    assert!(tables.check(store, "blåbærsyltetøj.dk").unwrap().unwrap().is_valid());
+    let report = tables.check(store, "ærø.fr").unwrap().unwrap();
+    let positions: Vec<usize> = report.disallowed.iter().map(|d| d.position).collect();
+    assert_eq!(positions, vec![2]);
+}
+
+#[test]
+fn test_unknown_tld_and_invalid_host() {
+    let store = SuffixStore::global().unwrap();
+    let tables = IdnTables::builtin();
+
+    assert_eq!(tables.check(store, "münchen.com").unwrap(), None);
+    assert!(tables.check(store, "not a host").is_err());
+}
+
+#[test]
+fn test_parse_and_insert_custom_table() {
+    let repertoire = Repertoire::parse("# custom\nU+0061..U+007A\nU+00E9 # é\nU+0030..U+0039\n").unwrap();
+    assert!(repertoire.contains('é'));
+    assert!(!repertoire.contains('-'));
+
+    let mut tables = IdnTables::default();
+    tables.insert("ελ", repertoire).unwrap();
+    assert!(tables.get("xn--qxam").is_some());
+
+    let report = tables.check(SuffixStore::global().unwrap(), "café-1.ελ").unwrap().unwrap();
+    assert_eq!(report.disallowed[0].character, '-');
+    assert_eq!(report.disallowed[0].position, 4);
+}
+
+#[test]
+fn test_parse_rejects_invalid_lines() {
+    assert_eq!(Repertoire::parse("U+0061\nU+00ZZ\n").unwrap_err(), "line 2: invalid code point or range 'U+00ZZ'");
+    assert!(Repertoire::parse("U+007A..U+0061").is_err());
+    assert!(Repertoire::parse("U+D800").is_err());
+    assert!(Repertoire::parse("0061").is_err());
+}
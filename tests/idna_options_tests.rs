@@ -0,0 +1,174 @@
+use domain_validator::domain_parser;
+use domain_validator::idna_options::IdnaOptions;
+use domain_validator::suffix_store::SuffixStore;
+
+fn options(change: impl FnOnce(&mut IdnaOptions)) -> IdnaOptions {
+    let mut options = IdnaOptions::default();
+    change(&mut options);
+    options
+}
+
+#[test]
+fn test_defaults_match_the_url_standard() {
+    let defaults = IdnaOptions::default();
+    for domain in [
+        "faß.de",
+        "Example.COM",
+        "例え。jp",
+        "_dmarc.example.com",
+        "-abc.de",
+        "a\u{200D}b.com",
+        "\u{915}\u{94D}\u{200D}\u{937}.in",
+        "a\u{05D0}.com",
+        "\u{05D0}\u{05D1}.com",
+        "1\u{05D0}.com",
+        "\u{0301}a.com",
+        "xn--r8jz45g.jp",
+        "\u{1F600}.com",
+        "a\u{FFFF}.com",
+    ] {
+        assert_eq!(
+            defaults.to_ascii(domain).ok(),
+            idna::domain_to_ascii(domain).ok(),
+            "{}",
+            domain
+        );
+    }
+    assert_eq!(defaults.to_ascii("例え。jp").unwrap(), "xn--r8jz45g.jp");
+}
+
+#[test]
+fn test_transitional_processing() {
+    assert_eq!(IdnaOptions::default().to_ascii("faß.de").unwrap(), "xn--fa-hia.de");
+    let transitional = options(|o| o.transitional_processing = true);
+    assert_eq!(transitional.to_ascii("faß.de").unwrap(), "fass.de");
+    assert_eq!(transitional.to_ascii("a\u{200D}b.com").unwrap(), "ab.com");
+}
+
+#[test]
+fn test_std3_ascii_rules() {
+    let std3 = options(|o| o.use_std3_ascii_rules = true);
+    let error = std3.to_ascii("_dmarc.example.com").unwrap_err();
+    assert!(
+        error.starts_with("Invalid domain name: '_dmarc.example.com' ("),
+        "{}",
+        error
+    );
+    assert!(error.contains("std3"), "{}", error);
+    assert!(std3.to_ascii("www.example.com").is_ok());
+}
+
+#[test]
+fn test_check_hyphens() {
+    assert!(IdnaOptions::default().to_ascii("-abc.de").is_ok());
+    let error = options(|o| o.check_hyphens = true).to_ascii("-abc.de").unwrap_err();
+    assert_eq!(error, "Invalid domain name: '-abc.de' (check_hyphens)");
+}
+
+#[test]
+fn test_check_bidi() {
+    // A left-to-right label may not contain a right-to-left letter
+    let domain = "a\u{05D0}.com";
+    let error = IdnaOptions::default().to_ascii(domain).unwrap_err();
+    assert!(error.contains("check_bidi"), "{}", error);
+    assert!(options(|o| o.check_bidi = false).to_ascii(domain).is_ok());
+}
+
+#[test]
+fn test_failed_checks_are_named() {
+    let hyphens = options(|o| o.check_hyphens = true);
+    assert_eq!(
+        hyphens.to_ascii("-a\u{05D0}.com").unwrap_err(),
+        "Invalid domain name: '-a\u{05D0}.com' (check_hyphens, check_bidi)"
+    );
+
+    // Each check is filtered on its own
+    let hyphens_only = options(|o| {
+        o.check_hyphens = true;
+        o.check_bidi = false;
+    });
+    assert_eq!(
+        hyphens_only.to_ascii("-a\u{05D0}.com").unwrap_err(),
+        "Invalid domain name: '-a\u{05D0}.com' (check_hyphens)"
+    );
+
+    let defaults = IdnaOptions::default();
+    assert!(defaults
+        .to_ascii("a\u{2615}\u{FFFF}.com")
+        .unwrap_err()
+        .ends_with("(disallowed_character)"));
+    assert!(defaults
+        .to_ascii("\u{0301}a.com")
+        .unwrap_err()
+        .ends_with("(start_combining_mark)"));
+}
+
+#[test]
+fn test_check_joiners() {
+    let no_joiners = options(|o| o.check_joiners = false);
+
+    // A joiner between Latin letters has no context
+    let error = IdnaOptions::default().to_ascii("a\u{200D}b.com").unwrap_err();
+    assert!(error.ends_with("(check_joiners)"), "{}", error);
+    assert_eq!(no_joiners.to_ascii("a\u{200D}b.com").unwrap(), "xn--ab-m1t.com");
+
+    // Allowed after a virama, and between joining Arabic letters
+    let joiners = IdnaOptions::default();
+    assert!(joiners.to_ascii("\u{915}\u{94D}\u{200D}\u{937}.in").is_ok());
+    assert!(joiners.to_ascii("\u{628}\u{200C}\u{628}.com").is_ok());
+    assert!(joiners.to_ascii("\u{627}\u{200C}\u{628}.com").is_err());
+}
+
+#[test]
+fn test_verify_dns_length() {
+    let long_label = format!("{}.com", "a".repeat(64));
+    assert!(IdnaOptions::default().to_ascii(&long_label).is_ok());
+
+    let dns = options(|o| o.verify_dns_length = true);
+    assert!(dns.to_ascii(&long_label).unwrap_err().contains("too_long_for_dns"));
+    assert!(dns.to_ascii("www..com").unwrap_err().contains("too_short_for_dns"));
+    assert!(dns.to_ascii("example.com.").is_ok());
+}
+
+#[test]
+fn test_store_options() {
+    let transitional = options(|o| o.transitional_processing = true);
+    let store = SuffixStore::embedded().with_idna_options(transitional);
+    assert_eq!(store.idna_options(), &transitional);
+
+    assert_eq!(store.extract_root_domain("www.faß.de"), Ok("fass.de".to_string()));
+    assert_eq!(
+        store.extract_root_domain_from_url("https://www.Faß.de/path"),
+        Ok("fass.de".to_string())
+    );
+
+    let default = SuffixStore::embedded();
+    assert_eq!(default.extract_root_domain("www.faß.de"), Ok("faß.de".to_string()));
+    assert_eq!(
+        default.extract_root_domain_from_url("https://www.faß.de/path"),
+        Ok("faß.de".to_string())
+    );
+    assert_eq!(
+        default.extract_root_domain_with("www.faß.de", &transitional),
+        Ok("fass.de".to_string())
+    );
+}
+
+#[test]
+fn test_parse_url_with_options() {
+    let no_bidi = options(|o| o.check_bidi = false);
+    let url = "https://user@www.a\u{05D0}.com:8443/path";
+
+    assert_ne!(domain_parser::parse_url(url).ok().as_deref(), Some("www.xn--a-0hc.com"));
+    assert_eq!(
+        domain_parser::parse_url_with(url, &no_bidi).unwrap(),
+        "www.xn--a-0hc.com"
+    );
+
+    // The rest of the URL is still validated
+    assert!(domain_parser::parse_url_with("https://www.a\u{05D0}.com:99999/", &no_bidi).is_err());
+    assert_eq!(
+        domain_parser::parse_url_with("https://www.example.com/", &no_bidi).unwrap(),
+        "www.example.com"
+    );
+}
@@ -0,0 +1,87 @@
+use domain_validator::lookalike::{Difference, ProtectedDomains};
+use domain_validator::suffix_store::SuffixStore;
+use std::fs;
+
+fn protected() -> ProtectedDomains {
+    let store = SuffixStore::global().unwrap();
+    ProtectedDomains::new(store, ["www.example.com", "paypal.com", "microsoft.com", "bank.co.uk"]).unwrap()
+}
+
+#[test]
+fn test_reduces_to_root_domains_and_deduplicates() {
+    let store = SuffixStore::global().unwrap();
+    let protected = ProtectedDomains::new(store, ["example.com", "www.example.com", "EXAMPLE.com"]).unwrap();
+    assert_eq!(protected.len(), 1);
+    assert!(ProtectedDomains::default().is_empty());
+}
+
+#[test]
+fn test_cyrillic_lookalike_is_matched() {
+    let store = SuffixStore::global().unwrap();
+    // Cyrillic а in place of the first a
+    let lookalike = protected().check(store, "login.p\u{430}ypal.com").unwrap().unwrap();
+
+    assert_eq!(lookalike.root_domain, "p\u{430}ypal.com");
+    assert_eq!(lookalike.protected_domain, "paypal.com");
+    assert_eq!(
+        lookalike.differences,
+        vec![Difference { position: 1, found: "\u{430}".to_string(), expected: "a".to_string() }]
+    );
+}
+
+#[test]
+fn test_punycode_and_multi_character_lookalikes() {
+    let store = SuffixStore::global().unwrap();
+    let protected = protected();
+
+    let punycode = protected.check(store, "xn--pypal-4ve.com").unwrap().unwrap();
+    assert_eq!(punycode.protected_domain, "paypal.com");
+
+    let rn = protected.check(store, "rnicrosoft.com").unwrap().unwrap();
+    assert_eq!(rn.protected_domain, "microsoft.com");
+    assert_eq!(
+        rn.differences,
+        vec![Difference { position: 0, found: "rn".to_string(), expected: "m".to_string() }]
+    );
+
+    let digit = protected.check(store, "paypa1.com").unwrap().unwrap();
+    assert_eq!(digit.differences[0].position, 5);
+    assert_eq!(digit.differences[0].found, "1");
+    assert_eq!(digit.differences[0].expected, "l");
+}
+
+#[test]
+fn test_protected_and_unrelated_domains_are_not_matched() {
+    let store = SuffixStore::global().unwrap();
+    let protected = protected();
+
+    assert_eq!(protected.check(store, "www.paypal.com").unwrap(), None);
+    assert_eq!(protected.check(store, "bank.co.uk").unwrap(), None);
+    assert_eq!(protected.check(store, "paypal.net").unwrap(), None);
+    assert_eq!(protected.check(store, "example.org").unwrap(), None);
+}
+
+#[test]
+fn test_check_url() {
+    let store = SuffixStore::global().unwrap();
+    let lookalike = protected().check_url(store, "https://www.b\u{430}nk.co.uk/login").unwrap().unwrap();
+    assert_eq!(lookalike.protected_domain, "bank.co.uk");
+    assert!(protected().check_url(store, "not a url").is_err());
+}
+
+#[test]
+fn test_from_file() {
+    let store = SuffixStore::global().unwrap();
+    let dir = std::env::temp_dir().join(format!("lookalike_tests_{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+
+    let path = dir.join("protected.txt");
+    fs::write(&path, "# brands\nexample.com\n\nwww.paypal.com\n").unwrap();
+    assert_eq!(ProtectedDomains::from_file(store, &path).unwrap().len(), 2);
+
+    fs::write(&path, "example.com\nnot a domain\n").unwrap();
+    let error = ProtectedDomains::from_file(store, &path).unwrap_err();
+    assert!(error.contains("protected.txt:2:"), "{}", error);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,113 @@
+use domain_validator::psl::Section;
+use domain_validator::psl_diff;
+use domain_validator::suffix_store::SuffixStore;
+
+const OLD_LIST: &str = "\
+// ===BEGIN ICANN DOMAINS===
+com
+uk
+co.uk
+// ===END ICANN DOMAINS===
+// ===BEGIN PRIVATE DOMAINS===
+github.io
+example.net
+// ===END PRIVATE DOMAINS===
+";
+
+const NEW_LIST: &str = "\
+// ===BEGIN ICANN DOMAINS===
+com
+uk
+co.uk
+example.net
+// ===END ICANN DOMAINS===
+// ===BEGIN PRIVATE DOMAINS===
+blogspot.com
+// ===END PRIVATE DOMAINS===
+";
+
+#[test]
+fn test_diff_rules_by_section() {
+    let sections = psl_diff::diff_rules(OLD_LIST, NEW_LIST);
+
+    assert_eq!(sections.len(), 2);
+
+    let icann = &sections[0];
+    assert_eq!(icann.section, Section::Icann);
+    assert!(icann.added.is_empty());
+    assert!(icann.removed.is_empty());
+    assert_eq!(icann.changed, vec!["example.net".to_string()]);
+
+    let private = &sections[1];
+    assert_eq!(private.section, Section::Private);
+    assert_eq!(private.added, vec!["blogspot.com".to_string()]);
+    assert_eq!(private.removed, vec!["github.io".to_string()]);
+    assert!(private.changed.is_empty());
+}
+
+#[test]
+fn test_diff_identical_lists() {
+    let diff = psl_diff::diff_lists(OLD_LIST, OLD_LIST, ["www.example.co.uk"]).unwrap();
+    assert!(diff.is_empty());
+}
+
+#[test]
+fn test_diff_rules_matches_unicode_and_punycode_spellings() {
+    let old = "// ===BEGIN ICANN DOMAINS===\ncn\n公司.cn\n*.東京.jp\n// ===END ICANN DOMAINS===\n";
+    let new = "// ===BEGIN ICANN DOMAINS===\ncn\nxn--55qx5d.cn\n*.xn--1lqs71d.jp\n// ===END ICANN DOMAINS===\n";
+    let diff = psl_diff::diff_lists(old, new, ["www.example.公司.cn"]).unwrap();
+    assert!(diff.is_empty(), "{}", diff);
+
+    // A real change is reported as written in its own list
+    let newer = "// ===BEGIN PRIVATE DOMAINS===\nxn--55qx5d.cn\n// ===END PRIVATE DOMAINS===\n";
+    let sections = psl_diff::diff_rules(old, newer);
+    assert_eq!(sections[0].removed, vec!["*.東京.jp".to_string(), "cn".to_string()]);
+    assert_eq!(sections[1].changed, vec!["xn--55qx5d.cn".to_string()]);
+}
+
+#[test]
+fn test_diff_hosts_reports_changed_root_domains() {
+    let hosts = ["www.example.com", "me.blogspot.com", "me.github.io", "www.example.co.uk"];
+    let diff = psl_diff::diff_lists(OLD_LIST, NEW_LIST, hosts).unwrap();
+
+    let changed: Vec<&str> = diff.hosts.iter().map(|h| h.host.as_str()).collect();
+    assert_eq!(changed, vec!["me.blogspot.com", "me.github.io"]);
+
+    assert_eq!(diff.hosts[0].old, Ok("blogspot.com".to_string()));
+    assert_eq!(diff.hosts[0].new, Ok("me.blogspot.com".to_string()));
+    assert_eq!(diff.hosts[1].old, Ok("me.github.io".to_string()));
+    assert_eq!(diff.hosts[1].new, Ok("github.io".to_string()));
+}
+
+#[test]
+fn test_diff_hosts_reports_new_errors() {
+    let old: SuffixStore = OLD_LIST.parse().unwrap();
+    let new: SuffixStore = NEW_LIST.parse().unwrap();
+
+    // example.net is a public suffix itself in the old list, but not in the new one either
+    let changes = psl_diff::diff_hosts(&old, &new, ["example.net"]);
+    assert!(changes.is_empty());
+
+    let changes = psl_diff::diff_hosts(&old, &new, ["github.io"]);
+    assert_eq!(changes.len(), 1);
+    assert!(changes[0].old.is_err());
+    assert!(changes[0].new.is_ok());
+}
+
+#[test]
+fn test_diff_report_lists_sections_and_hosts() {
+    let diff = psl_diff::diff_lists(OLD_LIST, NEW_LIST, ["me.github.io"]).unwrap();
+    let report = diff.to_string();
+
+    assert!(report.contains("ICANN rules: 0 added, 0 removed, 1 changed"));
+    assert!(report.contains("PRIVATE rules: 1 added, 1 removed, 0 changed"));
+    assert!(report.contains("  + blogspot.com"));
+    assert!(report.contains("  - github.io"));
+    assert!(report.contains("  me.github.io: me.github.io -> github.io"));
+}
+
+#[test]
+fn test_diff_invalid_list() {
+    let result = psl_diff::diff_lists("", NEW_LIST, ["www.example.com"]);
+    assert!(result.is_err());
+}
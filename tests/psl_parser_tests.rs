@@ -0,0 +1,139 @@
+use domain_validator::psl::{self, ParseMode, PslErrorKind, RuleKind, Section};
+use domain_validator::suffix_store::SuffixStore;
+use std::fs;
+
+fn list(rules: &str) -> String {
+    format!(
+        "// header\n// ===BEGIN ICANN DOMAINS===\n{}\n// ===END ICANN DOMAINS===\n",
+        rules
+    )
+}
+
+#[test]
+fn test_parse_bundled_list_strict() {
+    let data = fs::read_to_string("public_suffix_list.dat").unwrap();
+    let parsed = psl::parse_list(&data, ParseMode::Strict).unwrap();
+
+    assert!(parsed.rules.len() > 9000);
+    assert!(parsed.diagnostics.is_empty());
+    assert_eq!(parsed.sections.len(), 2);
+    assert_eq!(parsed.sections[0].section, Section::Icann);
+    assert_eq!(parsed.sections[1].section, Section::Private);
+    assert!(parsed.sections.iter().all(|s| s.end_line.is_some()));
+}
+
+#[test]
+fn test_parse_rule_kinds_and_lines() {
+    let parsed = psl::parse_list(&list("ck\n*.ck\n!www.ck"), ParseMode::Strict).unwrap();
+
+    let kinds: Vec<(RuleKind, usize)> = parsed.rules.iter().map(|r| (r.kind, r.line)).collect();
+    assert_eq!(
+        kinds,
+        vec![(RuleKind::Normal, 3), (RuleKind::Wildcard, 4), (RuleKind::Exception, 5)]
+    );
+}
+
+#[test]
+fn test_parse_keeps_section_markers() {
+    let data = "// ===BEGIN ICANN DOMAINS===\ncom\n// ===END ICANN DOMAINS===\n\
+                // ===BEGIN PRIVATE DOMAINS===\ngithub.io\n// ===END PRIVATE DOMAINS===\n";
+    let parsed = psl::parse_list(data, ParseMode::Strict).unwrap();
+
+    assert_eq!(parsed.sections[0].begin_line, 1);
+    assert_eq!(parsed.sections[0].end_line, Some(3));
+    assert_eq!(parsed.sections[1].begin_line, 4);
+    assert_eq!(parsed.sections[1].end_line, Some(6));
+    assert_eq!(parsed.rules[1].section, Section::Private);
+}
+
+#[test]
+fn test_parse_ignores_rules_outside_sections() {
+    let parsed = psl::parse_list("com\n// ===BEGIN ICANN DOMAINS===\nnet\n", ParseMode::Strict).unwrap();
+    assert_eq!(parsed.rules.len(), 1);
+    assert_eq!(parsed.rules[0].text, "net");
+}
+
+#[test]
+fn test_parse_invalid_character() {
+    let errors = psl::parse_list(&list("com\nexa_mple.com"), ParseMode::Strict).unwrap_err();
+
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].line, 4);
+    assert_eq!(errors[0].kind, PslErrorKind::InvalidCharacter('_'));
+}
+
+#[test]
+fn test_parse_empty_label() {
+    let errors = psl::parse_list(&list("co..uk\n.com"), ParseMode::Strict).unwrap_err();
+
+    assert_eq!(errors.len(), 2);
+    assert!(errors.iter().all(|e| e.kind == PslErrorKind::EmptyLabel));
+}
+
+#[test]
+fn test_parse_misplaced_wildcard() {
+    let errors = psl::parse_list(&list("foo.*.jp\n*\n!*.jp\nb*.jp"), ParseMode::Strict).unwrap_err();
+
+    assert_eq!(errors.len(), 4);
+    assert!(errors.iter().all(|e| e.kind == PslErrorKind::MisplacedWildcard));
+}
+
+#[test]
+fn test_parse_duplicate_rule() {
+    let errors = psl::parse_list(&list("com\nnet\nCOM"), ParseMode::Strict).unwrap_err();
+
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].line, 5);
+    assert_eq!(errors[0].kind, PslErrorKind::DuplicateRule { first_line: 3 });
+}
+
+#[test]
+fn test_parse_duplicate_unicode_and_punycode_rule() {
+    let errors = psl::parse_list(&list("公司.cn\nxn--55qx5d.cn"), ParseMode::Strict).unwrap_err();
+    assert_eq!(errors[0].kind, PslErrorKind::DuplicateRule { first_line: 3 });
+}
+
+#[test]
+fn test_parse_exception_without_wildcard() {
+    let errors = psl::parse_list(&list("jp\n!city.kobe.jp\n!www"), ParseMode::Strict).unwrap_err();
+
+    assert_eq!(errors.len(), 2);
+    assert_eq!(errors[0].line, 4);
+    assert_eq!(errors[0].kind, PslErrorKind::ExceptionWithoutWildcard);
+    assert_eq!(errors[1].line, 5);
+}
+
+#[test]
+fn test_parse_exception_before_wildcard() {
+    let parsed = psl::parse_list(&list("!city.kobe.jp\n*.kobe.jp"), ParseMode::Strict).unwrap();
+    assert_eq!(parsed.rules.len(), 2);
+}
+
+#[test]
+fn test_parse_lenient_skips_bad_lines() {
+    let parsed = psl::parse_list(&list("com\nbad_rule.com\nnet\nnet"), ParseMode::Lenient).unwrap();
+
+    let rules: Vec<&str> = parsed.rules.iter().map(|r| r.text.as_str()).collect();
+    assert_eq!(rules, vec!["com", "net"]);
+    assert_eq!(parsed.diagnostics.len(), 2);
+    assert_eq!(parsed.diagnostics[0].line, 4);
+    assert_eq!(parsed.diagnostics[1].line, 6);
+}
+
+#[test]
+fn test_parse_error_display() {
+    let errors = psl::parse_list(&list("a.*.com"), ParseMode::Strict).unwrap_err();
+    assert_eq!(errors[0].to_string(), "line 3: misplaced wildcard in rule 'a.*.com'");
+}
+
+#[test]
+fn test_store_reports_malformed_entry() {
+    let result = list("com\nexa mple.com\nfoo..com\n").parse::<SuffixStore>();
+
+    match result {
+        Ok(_) => panic!("malformed list was accepted"),
+        Err(e) => {
+            assert!(e.starts_with("Failed to parse Public Suffix List: line 5: empty label"));
+        }
+    }
+}
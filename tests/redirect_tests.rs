@@ -0,0 +1,151 @@
+use domain_validator::redirect::{Hop, UnwrapRule, Unwrapper};
+use domain_validator::suffix_store::SuffixStore;
+use std::fs;
+
+/// Unwraps a URL with the built-in rules
+fn unwrap(url: &str) -> Vec<Hop> {
+    Unwrapper::builtin().unwrap_url(SuffixStore::global().unwrap(), url).unwrap()
+}
+
+/// The root domain of the destination of a URL
+fn destination(url: &str) -> Option<String> {
+    unwrap(url).last().unwrap().root_domain.clone()
+}
+
+#[test]
+fn test_builtin_wrappers() {
+    for (url, rule, expected) in [
+        ("google.com/url?q=https://www.example.com/path", "google", "example.com"),
+        ("https://www.google.co.uk/url?sa=t&url=https%3A%2F%2Fexample.org%2F", "google", "example.org"),
+        (
+            "https://nam12.safelinks.protection.outlook.com/?url=https%3A%2F%2Fwww.example.com%2Fpath&data=05%7C01",
+            "outlook-safelinks",
+            "example.com",
+        ),
+        ("https://l.facebook.com/l.php?u=https%3A%2F%2Fexample.net%2F&h=AT0", "facebook", "example.net"),
+        ("https://urldefense.proofpoint.com/v1/url?u=https://example.co.uk/&k=abc", "proofpoint-v1", "example.co.uk"),
+        (
+            "https://urldefense.proofpoint.com/v2/url?u=https-3A__www.example.com_path-3Fa-3D1&d=DwMF&c=abc",
+            "proofpoint-v2",
+            "example.com",
+        ),
+        ("https://login.example.com/logout?redirect=https://evil.example.net/", "generic", "example.net"),
+    ] {
+        let chain = unwrap(url);
+        assert_eq!(chain.len(), 2, "{}", url);
+        assert_eq!(chain[0].rule.as_deref(), Some(rule), "{}", url);
+        assert_eq!(chain[1].root_domain.as_deref(), Some(expected), "{}", url);
+        assert_eq!(chain[1].rule, None);
+    }
+}
+
+#[test]
+fn test_proofpoint_v3() {
+    let chain = unwrap("https://urldefense.com/v3/__https://www.example.com/x?a=1*b=2__;Jg!!token$");
+    assert_eq!(chain[1].url, "https://www.example.com/x?a=1&b=2");
+
+    let chain = unwrap("https://urldefense.com/v3/__https://www.example.com/x?a=1**Ab=2__;JiY!!token$");
+    assert_eq!(chain[1].url, "https://www.example.com/x?a=1&&b=2");
+
+    let chain = unwrap("https://urldefense.com/v3/__https://example.org/__;!!token$");
+    assert_eq!(chain[1].root_domain.as_deref(), Some("example.org"));
+
+    // The embedded URL is decoded as written, not as the URL parser normalizes it
+    let chain = unwrap("https://urldefense.com/v3/__https://example.com/a/../b c__;!!token$");
+    assert_eq!(chain[1].url, "https://example.com/a/../b c");
+}
+
+#[test]
+fn test_nested_wrappers() {
+    let google = "https://www.google.com/url?q=https%3A%2F%2Fwww.example.com%2F";
+    let safelink = format!(
+        "https://eur01.safelinks.protection.outlook.com/?url={}&data=1",
+        google.replace('%', "%25").replace(':', "%3A").replace('/', "%2F").replace('?', "%3F").replace('=', "%3D")
+    );
+
+    let chain = unwrap(&safelink);
+    let domains: Vec<_> = chain.iter().map(|hop| hop.root_domain.as_deref().unwrap()).collect();
+    assert_eq!(domains, vec!["outlook.com", "google.com", "example.com"]);
+    let rules: Vec<_> = chain.iter().map(|hop| hop.rule.as_deref()).collect();
+    assert_eq!(rules, vec![Some("outlook-safelinks"), Some("google"), None]);
+}
+
+#[test]
+fn test_unwrap_stops_when_nothing_to_follow() {
+    // Searches and relative redirects are not followed
+    assert_eq!(unwrap("https://www.google.com/url?q=hello+world").len(), 1);
+    assert_eq!(unwrap("https://www.example.com/login?next=/home&url=/home").len(), 1);
+    assert_eq!(destination("https://www.example.com/path"), Some("example.com".to_string()));
+
+    // A Google host with another path is not a redirect
+    assert_eq!(unwrap("https://www.google.com/search?q=https://example.com/").len(), 1);
+
+    assert!(Unwrapper::builtin().unwrap_url(SuffixStore::global().unwrap(), "not a url").is_err());
+}
+
+#[test]
+fn test_unwrap_hop_limit() {
+    let mut url = "https://www.example.com/".to_string();
+    for _ in 0..20 {
+        url = format!("https://r.example.net/?url={}", url.replace('%', "%25").replace('&', "%26").replace('?', "%3F").replace('=', "%3D"));
+    }
+
+    let chain = unwrap(&url);
+    assert_eq!(chain.len(), 10);
+    assert_eq!(chain[9].rule, None);
+}
+
+#[test]
+fn test_user_rules() {
+    let store = SuffixStore::global().unwrap();
+    let url = "https://gateway.mycorp.com/r?to=https%3A%2F%2Fexample.com%2F";
+
+    let mut unwrapper = Unwrapper::new();
+    assert_eq!(unwrapper.unwrap_url(store, url).unwrap().len(), 1);
+
+    unwrapper.add_rule(UnwrapRule::query("mycorp", "mycorp.com", Some("/r"), &["to"]));
+    let chain = unwrapper.unwrap_url(store, url).unwrap();
+    assert_eq!(chain[0].rule.as_deref(), Some("mycorp"));
+    assert_eq!(chain[1].root_domain.as_deref(), Some("example.com"));
+}
+
+#[test]
+fn test_oauth_redirect_is_opt_in() {
+    let store = SuffixStore::global().unwrap();
+    let url = "https://accounts.example.com/o/oauth2/auth?client_id=1&redirect_uri=https%3A%2F%2Fapp.example.net%2Fcallback";
+
+    let chain = unwrap(url);
+    assert_eq!(chain.len(), 1);
+    assert_eq!(chain[0].rule, None);
+
+    let mut unwrapper = Unwrapper::builtin();
+    unwrapper.add_rule(UnwrapRule::oauth_redirect());
+    let chain = unwrapper.unwrap_url(store, url).unwrap();
+    assert_eq!(chain[0].rule.as_deref(), Some("oauth-redirect"));
+    assert_eq!(chain[1].root_domain.as_deref(), Some("example.net"));
+}
+
+#[test]
+fn test_load_rules() {
+    let store = SuffixStore::global().unwrap();
+    let dir = std::env::temp_dir().join(format!("redirect_tests_{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+
+    let path = dir.join("rules.txt");
+    fs::write(&path, "# name host path parameters\nmycorp gateway.mycorp.com * target,to\n").unwrap();
+    let mut unwrapper = Unwrapper::builtin();
+    unwrapper.load_rules(&path).unwrap();
+    assert_eq!(unwrapper.rules()[0].name, "mycorp");
+
+    // User rules are tried before the generic one, which would take `target` too
+    let chain = unwrapper
+        .unwrap_url(store, "https://gateway.mycorp.com/any?to=https://example.com/")
+        .unwrap();
+    assert_eq!(chain[0].rule.as_deref(), Some("mycorp"));
+
+    fs::write(&path, "mycorp gateway.mycorp.com\n").unwrap();
+    let error = unwrapper.load_rules(&path).unwrap_err();
+    assert!(error.contains("rules.txt:1:"), "{}", error);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,207 @@
+use domain_validator::psl::{self, ParseMode, ParsedList, Section};
+use domain_validator::suffix_trie::{SuffixMatch, SuffixTrie, FORMAT_VERSION, MAGIC};
+use publicsuffix::{List, Psl};
+use std::collections::BTreeSet;
+use std::fs;
+
+const HOSTS: &[&str] = &[
+    "www.example.com",
+    "example.co.uk",
+    "a.b.c.example.co.uk",
+    "myblog.blogspot.com",
+    "www.parliament.uk",
+    "test.github.io",
+    "www.ck",
+    "foo.www.ck",
+    "city.kawasaki.jp",
+    "www.city.kawasaki.jp",
+    "foo.kawasaki.jp",
+    "xn--mnchen-3ya.de",
+    "www.xn--1lqs71d.jp",
+    "shop.xn--55qx5d.cn",
+    "example.unknowntld",
+    "localhost",
+    "co.uk",
+    "WWW.EXAMPLE.COM",
+];
+
+fn bundled_list() -> ParsedList {
+    let data = fs::read_to_string("public_suffix_list.dat").unwrap();
+    psl::parse_list(&data, ParseMode::Strict).unwrap()
+}
+
+#[test]
+fn test_round_trip_rules_match_text_parser() {
+    let parsed = bundled_list();
+    let trie = SuffixTrie::compile(&parsed).unwrap();
+    let loaded = SuffixTrie::from_bytes(trie.as_bytes().to_vec()).unwrap();
+
+    let expected: BTreeSet<(String, Section)> = parsed
+        .rules
+        .iter()
+        .map(|rule| {
+            let (prefix, body) = match rule.text.strip_prefix('!') {
+                Some(body) => ("!", body),
+                None => ("", rule.text.as_str()),
+            };
+            let (wildcard, body) = match body.strip_prefix("*.") {
+                Some(body) => ("*.", body),
+                None => ("", body),
+            };
+            let ascii = idna::domain_to_ascii(body).unwrap();
+            (format!("{}{}{}", prefix, wildcard, ascii), rule.section)
+        })
+        .collect();
+    let actual: BTreeSet<(String, Section)> =
+        loaded.rules().into_iter().map(|rule| (rule.text, rule.section)).collect();
+
+    assert_eq!(actual, expected);
+    assert_eq!(loaded.rule_counts(), trie.rule_counts());
+    assert_eq!(loaded.version(), parsed.version.as_deref());
+    assert_eq!(loaded.commit(), parsed.commit.as_deref());
+}
+
+#[test]
+fn test_recompiling_decompiled_rules_is_byte_identical() {
+    let trie = SuffixTrie::compile(&bundled_list()).unwrap();
+
+    let mut text = format!(
+        "// VERSION: {}\n// COMMIT: {}\n",
+        trie.version().unwrap(),
+        trie.commit().unwrap()
+    );
+    for section in [Section::Icann, Section::Private] {
+        text.push_str(&format!("// ===BEGIN {} DOMAINS===\n", section));
+        for rule in trie.rules().iter().filter(|rule| rule.section == section) {
+            text.push_str(&rule.text);
+            text.push('\n');
+        }
+        text.push_str(&format!("// ===END {} DOMAINS===\n", section));
+    }
+
+    let recompiled = SuffixTrie::compile(&psl::parse_list(&text, ParseMode::Strict).unwrap()).unwrap();
+    assert_eq!(recompiled.as_bytes(), trie.as_bytes());
+}
+
+#[test]
+fn test_loaded_trie_matches_compiled_trie() {
+    let trie = SuffixTrie::compile(&bundled_list()).unwrap();
+    let loaded = SuffixTrie::from_bytes(trie.as_bytes()).unwrap();
+
+    for host in HOSTS {
+        assert_eq!(loaded.find(host), trie.find(host), "{}", host);
+        assert_eq!(loaded.registrable_domain(host), trie.registrable_domain(host), "{}", host);
+    }
+}
+
+#[test]
+fn test_matches_publicsuffix_crate() {
+    let data = fs::read_to_string("public_suffix_list.dat").unwrap();
+    let list: List = data.parse().unwrap();
+    let trie = SuffixTrie::compile(&bundled_list()).unwrap();
+
+    for host in HOSTS {
+        let expected = list
+            .domain(host.to_lowercase().as_bytes())
+            .map(|domain| String::from_utf8(domain.as_bytes().to_vec()).unwrap());
+        let actual = trie.registrable_domain(host).map(str::to_lowercase);
+        assert_eq!(actual, expected, "{}", host);
+    }
+}
+
+#[test]
+fn test_find_sections() {
+    let trie = SuffixTrie::compile(&bundled_list()).unwrap();
+
+    assert_eq!(
+        trie.find("www.example.co.uk"),
+        Some(SuffixMatch { labels: 2, section: Some(Section::Icann) })
+    );
+    assert_eq!(
+        trie.find("me.github.io"),
+        Some(SuffixMatch { labels: 2, section: Some(Section::Private) })
+    );
+    assert_eq!(
+        trie.find("example.unknowntld"),
+        Some(SuffixMatch { labels: 1, section: None })
+    );
+}
+
+#[test]
+fn test_wildcard_considered_next_to_exact_label() {
+    // *.wc.psl.hrsn.dev must match even though *.sub.wc.psl.hrsn.dev adds a "sub" node
+    let trie = SuffixTrie::compile(&bundled_list()).unwrap();
+
+    assert_eq!(trie.suffix("sub.wc.psl.hrsn.dev"), Some("sub.wc.psl.hrsn.dev"));
+    assert_eq!(trie.registrable_domain("x.sub.wc.psl.hrsn.dev"), None);
+    assert_eq!(trie.registrable_domain("ignored.wc.psl.hrsn.dev"), Some("ignored.wc.psl.hrsn.dev"));
+}
+
+#[test]
+fn test_lookups_borrow_from_input() {
+    let trie = SuffixTrie::compile(&bundled_list()).unwrap();
+    let host = String::from("www.example.co.uk.");
+
+    let domain = trie.registrable_domain(&host).unwrap();
+    assert_eq!(domain, "example.co.uk.");
+    assert_eq!(trie.suffix(&host), Some("co.uk."));
+    assert_eq!(domain.as_ptr(), host[4..].as_ptr());
+}
+
+#[test]
+fn test_find_rejects_empty_labels() {
+    let trie = SuffixTrie::compile(&bundled_list()).unwrap();
+
+    assert_eq!(trie.find(""), None);
+    assert_eq!(trie.find("www..example.com"), None);
+    assert_eq!(trie.find(".example.com"), None);
+}
+
+#[test]
+fn test_from_bytes_rejects_invalid_data() {
+    let trie = SuffixTrie::compile(&bundled_list()).unwrap();
+    let bytes = trie.as_bytes();
+
+    assert!(SuffixTrie::from_bytes(&b"not a trie"[..]).is_err());
+
+    let mut wrong_magic = bytes.to_vec();
+    wrong_magic[..4].copy_from_slice(b"XXXX");
+    assert!(SuffixTrie::from_bytes(wrong_magic).is_err());
+
+    let mut wrong_version = bytes.to_vec();
+    wrong_version[4..6].copy_from_slice(&(FORMAT_VERSION + 1).to_le_bytes());
+    let error = SuffixTrie::from_bytes(wrong_version).unwrap_err();
+    assert!(error.contains("Unsupported compiled suffix trie version"));
+
+    assert!(SuffixTrie::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+}
+
+#[test]
+fn test_from_bytes_rejects_corrupted_nodes() {
+    let trie = SuffixTrie::compile(&bundled_list()).unwrap();
+    let mut bytes = trie.as_bytes().to_vec();
+    assert_eq!(&bytes[..4], MAGIC);
+
+    // Point the root's children back at the root itself
+    let version_len = u16::from_le_bytes([bytes[24], bytes[25]]) as usize;
+    let commit_len = u16::from_le_bytes([bytes[26], bytes[27]]) as usize;
+    let root = 28 + version_len + commit_len;
+    bytes[root + 4..root + 8].copy_from_slice(&0u32.to_le_bytes());
+
+    assert!(SuffixTrie::from_bytes(bytes).is_err());
+}
+
+#[test]
+fn test_compile_rejects_fields_too_long_for_the_binary_form() {
+    let section = |rules: &str| format!("// ===BEGIN ICANN DOMAINS===\n{}\n// ===END ICANN DOMAINS===\n", rules);
+
+    let long_label = format!("{}.com", "a".repeat(300));
+    let parsed = psl::parse_list(&section(&format!("com\n{}", long_label)), ParseMode::Lenient).unwrap();
+    let error = SuffixTrie::compile(&parsed).unwrap_err();
+    assert!(error.starts_with("Failed to compile Public Suffix List: label 'aaa"), "{}", error);
+
+    let long_version = format!("// VERSION: {}\n{}", "1".repeat(70_000), section("com"));
+    let parsed = psl::parse_list(&long_version, ParseMode::Lenient).unwrap();
+    let error = SuffixTrie::compile(&parsed).unwrap_err();
+    assert!(error.contains("the version is too long (70000 does not fit in 2 bytes)"), "{}", error);
+}
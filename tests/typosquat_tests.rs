@@ -0,0 +1,112 @@
+use domain_validator::domain::RootDomain;
+use domain_validator::suffix_store::SuffixStore;
+use domain_validator::typosquat::{generate, Technique, Typosquat};
+use std::collections::HashSet;
+
+fn candidates(root: &str, technique: Technique) -> Vec<Typosquat> {
+    let root = RootDomain::parse(root).unwrap();
+    generate(SuffixStore::global().unwrap(), &root, &[technique])
+}
+
+fn domains(root: &str, technique: Technique) -> Vec<String> {
+    candidates(root, technique).into_iter().map(|c| c.domain).collect()
+}
+
+#[test]
+fn test_omission_and_repetition() {
+    let omissions = domains("example.com", Technique::Omission);
+    assert_eq!(omissions.len(), 7);
+    assert!(omissions.contains(&"exmple.com".to_string()));
+    assert!(omissions.contains(&"xample.com".to_string()));
+
+    let repetitions = domains("example.com", Technique::Repetition);
+    assert!(repetitions.contains(&"exammple.com".to_string()));
+    assert!(repetitions.contains(&"examplee.com".to_string()));
+}
+
+#[test]
+fn test_transposition_skips_identical_pairs() {
+    let transpositions = domains("google.com", Technique::Transposition);
+    assert!(transpositions.contains(&"goolge.com".to_string()));
+    assert!(transpositions.contains(&"ogogle.com".to_string()));
+    assert!(!transpositions.contains(&"google.com".to_string()));
+    assert_eq!(transpositions.len(), 4);
+}
+
+#[test]
+fn test_adjacent_key() {
+    let adjacent = domains("example.com", Technique::AdjacentKey);
+    assert!(adjacent.contains(&"wxample.com".to_string()));
+    assert!(adjacent.contains(&"exsmple.com".to_string()));
+    assert!(!adjacent.contains(&"exbmple.com".to_string()));
+}
+
+#[test]
+fn test_bitsquatting_stays_in_hostname_alphabet() {
+    let flipped = domains("example.com", Technique::Bitsquatting);
+    assert!(flipped.contains(&"uxample.com".to_string()));
+    assert!(flipped.contains(&"exaeple.com".to_string()));
+    assert!(flipped
+        .iter()
+        .all(|d| d.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '.')));
+}
+
+#[test]
+fn test_homoglyph_includes_unicode_and_multi_character_lookalikes() {
+    let homoglyphs = candidates("microsoft.com", Technique::Homoglyph);
+    let cyrillic = homoglyphs.iter().find(|c| c.domain == "micrоsoft.com").unwrap();
+    assert!(cyrillic.ascii.starts_with("xn--"));
+    assert!(homoglyphs.iter().any(|c| c.domain == "rnicrosoft.com"));
+    assert!(homoglyphs.iter().any(|c| c.domain == "micr0soft.com"));
+}
+
+#[test]
+fn test_hyphenation_and_vowel_swap() {
+    let hyphenated = domains("example.com", Technique::Hyphenation);
+    assert_eq!(hyphenated.len(), 6);
+    assert!(hyphenated.contains(&"exam-ple.com".to_string()));
+
+    let swapped = domains("example.com", Technique::VowelSwap);
+    assert!(swapped.contains(&"exomple.com".to_string()));
+    assert!(swapped.contains(&"ixample.com".to_string()));
+}
+
+#[test]
+fn test_alternate_suffixes_come_from_the_list() {
+    let alternates = domains("example.co.uk", Technique::AlternateSuffix);
+    assert!(alternates.contains(&"example.com".to_string()));
+    assert!(alternates.contains(&"example.org.uk".to_string()));
+    assert!(alternates.contains(&"example.uk".to_string()));
+    assert!(!alternates.contains(&"example.co.uk".to_string()));
+    assert!(!alternates.contains(&"example.com.au".to_string()));
+}
+
+#[test]
+fn test_name_techniques_keep_multi_label_suffix() {
+    for candidate in candidates("example.co.uk", Technique::Omission) {
+        assert!(candidate.domain.ends_with(".co.uk"), "{}", candidate.domain);
+    }
+}
+
+#[test]
+fn test_all_techniques_are_deduplicated_and_labelled() {
+    let root = RootDomain::parse("example.com").unwrap();
+    let all = generate(SuffixStore::global().unwrap(), &root, &Technique::ALL);
+
+    let unique: HashSet<&str> = all.iter().map(|c| c.ascii.as_str()).collect();
+    assert_eq!(unique.len(), all.len());
+    assert!(!unique.contains("example.com"));
+
+    let techniques: HashSet<Technique> = all.iter().map(|c| c.technique).collect();
+    assert_eq!(techniques.len(), Technique::ALL.len());
+}
+
+#[test]
+fn test_technique_names_round_trip() {
+    for technique in Technique::ALL {
+        assert_eq!(technique.to_string().parse::<Technique>().unwrap(), technique);
+        assert_eq!(serde_json::to_value(technique).unwrap(), technique.to_string());
+    }
+    assert_eq!("vowel-swap".parse::<Technique>().unwrap(), Technique::VowelSwap);
+    assert!("swap".parse::<Technique>().is_err());
+}
@@ -0,0 +1,106 @@
+use domain_validator::suffix_store::SuffixStore;
+use domain_validator::url_diagnostics::{apply_suggestions, diagnose, diagnose_with, DiagnosticKind, Span};
+
+/// The kind, covered text and suggestion of each diagnostic
+fn summary(url: &str) -> Vec<(DiagnosticKind, &str, Option<String>)> {
+    diagnose(url)
+        .into_iter()
+        .map(|d| (d.kind, &url[d.span.start..d.span.end], d.suggestion))
+        .collect()
+}
+
+#[test]
+fn test_diagnose_locates_each_problem() {
+    let url = "htp:/exa mple..com";
+    let diagnostics = diagnose(url);
+
+    let kinds: Vec<_> = diagnostics.iter().map(|d| d.kind).collect();
+    assert_eq!(
+        kinds,
+        vec![
+            DiagnosticKind::MisspelledScheme,
+            DiagnosticKind::MalformedSchemeSeparator,
+            DiagnosticKind::Whitespace,
+            DiagnosticKind::EmptyLabel,
+        ]
+    );
+    assert_eq!(diagnostics[0].span, Span { start: 0, end: 3 });
+    assert_eq!(diagnostics[1].span, Span { start: 3, end: 5 });
+    assert_eq!(diagnostics[2].span, Span { start: 8, end: 9 });
+    assert_eq!(diagnostics[3].span, Span { start: 14, end: 15 });
+    assert_eq!(diagnostics[1].message, "missing '/' after 'htp:'");
+
+    assert_eq!(apply_suggestions(url, &diagnostics).as_deref(), Some("http://example.com"));
+}
+
+#[test]
+fn test_diagnose_scheme_separator() {
+    assert_eq!(
+        summary("http//example.com"),
+        vec![(DiagnosticKind::MalformedSchemeSeparator, "//", Some("://".to_string()))]
+    );
+    assert_eq!(
+        summary("HTTPS:///example.com"),
+        vec![(DiagnosticKind::MalformedSchemeSeparator, ":///", Some("://".to_string()))]
+    );
+    assert_eq!(summary("htps://example.com"), vec![(DiagnosticKind::MisspelledScheme, "htps", Some("https".to_string()))]);
+
+    // Other schemes and hosts with ports are left alone
+    assert!(diagnose("ftp://example.com/file").is_empty());
+    assert!(diagnose("example.com:8080/path").is_empty());
+}
+
+#[test]
+fn test_diagnose_host() {
+    assert_eq!(
+        summary(" https://.example.com/a b "),
+        vec![
+            (DiagnosticKind::Whitespace, " ", Some(String::new())),
+            (DiagnosticKind::EmptyLabel, ".", Some(String::new())),
+            (DiagnosticKind::Whitespace, " ", Some(String::new())),
+        ]
+    );
+    assert_eq!(summary("https://ex!ample.com"), vec![(DiagnosticKind::InvalidCharacter, "!", None)]);
+    assert_eq!(
+        summary("https://example.com:99999/"),
+        vec![(DiagnosticKind::InvalidPort, ":99999", Some(String::new()))]
+    );
+    assert_eq!(summary("https://"), vec![(DiagnosticKind::MissingHost, "", None)]);
+
+    // Valid URLs have no diagnostics
+    for url in ["https://www.example.com/path?q=1", "user@example.com:8080", "https://münchen.de./", "https://[::1]/", "_dmarc.example.com"] {
+        assert!(diagnose(url).is_empty(), "{}", url);
+    }
+}
+
+#[test]
+fn test_diagnose_leaves_opaque_uris_alone() {
+    for url in ["data:text/html,hi", "javascript:alert(1)", "mailto:user@example.com", "MAILTO:a b@example.com"] {
+        let diagnostics = diagnose(url);
+        assert!(diagnostics.is_empty(), "{}: {:?}", url, diagnostics);
+        assert_eq!(apply_suggestions(url, &diagnostics), None);
+    }
+
+    // Only the surrounding whitespace is reported
+    let url = " mailto:user@example.com";
+    assert_eq!(summary(url), vec![(DiagnosticKind::Whitespace, " ", Some(String::new()))]);
+    assert_eq!(apply_suggestions(url, &diagnose(url)).as_deref(), Some("mailto:user@example.com"));
+}
+
+#[test]
+fn test_diagnose_with_suggests_close_tld() {
+    let store = SuffixStore::global().unwrap();
+
+    let url = "https://www.example.cmo/";
+    let diagnostics = diagnose_with(store, url);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].kind, DiagnosticKind::UnknownTld);
+    assert_eq!(&url[diagnostics[0].span.start..diagnostics[0].span.end], "cmo");
+    assert_eq!(diagnostics[0].suggestion.as_deref(), Some("com"));
+    assert_eq!(diagnostics[0].to_string(), "bytes 20..23: 'cmo' is not a top-level domain (did you mean 'com'?)");
+
+    assert_eq!(diagnose_with(store, "example.comm")[0].suggestion.as_deref(), Some("com"));
+    assert_eq!(diagnose_with(store, "example.zzzzzz")[0].suggestion, None);
+    assert!(diagnose_with(store, "https://www.example.co.uk/").is_empty());
+    assert!(diagnose_with(store, "https://localhost/").is_empty());
+}